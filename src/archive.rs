@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 归档中的一次抓取
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedPage {
+    pub url: String,
+    /// 抓取时间（Unix 毫秒）
+    pub fetched_at: u64,
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    /// 解压、解码后的 HTML
+    pub body: String,
+}
+
+impl ArchivedPage {
+    pub fn new(url: &str, status: u16, headers: BTreeMap<String, String>, body: &str) -> Self {
+        let fetched_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        Self {
+            url: url.to_string(),
+            fetched_at,
+            status,
+            headers,
+            body: body.to_string(),
+        }
+    }
+
+    /// 抓取当天的本地日期，用于把 "N天以前发布" 换算成日期
+    pub fn fetched_on(&self) -> NaiveDate {
+        DateTime::from_timestamp_millis(self.fetched_at as i64)
+            .map(|t| t.with_timezone(&Local).date_naive())
+            .unwrap_or_else(|| Local::now().date_naive())
+    }
+}
+
+/// 原始页面归档：每次抓取保存为目录下的一个 gzip 压缩的 JSON 文件
+///
+/// 网站改版后可以用 `reparse` 子命令对归档重新解析，不需要再次爬取。
+#[derive(Debug, Clone)]
+pub struct Archive {
+    dir: PathBuf,
+}
+
+impl Archive {
+    pub fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir).with_context(|| format!("创建归档目录失败: {}", dir.display()))?;
+        Ok(Self { dir: dir.to_path_buf() })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 保存一次抓取，文件名由抓取时间和URL组成，同一页的多次抓取互不覆盖
+    pub fn store(&self, page: &ArchivedPage) -> Result<PathBuf> {
+        let path = self.dir.join(format!("{}_{}.json.gz", page.fetched_at, file_slug(&page.url)));
+        let file = File::create(&path).with_context(|| format!("创建归档文件失败: {}", path.display()))?;
+        let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+        serde_json::to_writer(&mut encoder, page)?;
+        encoder.finish()?;
+        Ok(path)
+    }
+
+    /// 按抓取时间顺序读取归档中的所有页面，无法读取的文件会被跳过
+    pub fn load_all(&self) -> Result<Vec<ArchivedPage>> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.dir)
+            .with_context(|| format!("读取归档目录失败: {}", self.dir.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.to_string_lossy().ends_with(".json.gz"))
+            .collect();
+        paths.sort();
+
+        let mut pages = Vec::new();
+        for path in paths {
+            match load_page(&path) {
+                Ok(page) => pages.push(page),
+                Err(e) => println!("跳过无法读取的归档文件 {}: {:#}", path.display(), e),
+            }
+        }
+        pages.sort_by_key(|page| page.fetched_at);
+        Ok(pages)
+    }
+}
+
+fn load_page(path: &Path) -> Result<ArchivedPage> {
+    let file = File::open(path)?;
+    let page = serde_json::from_reader(GzDecoder::new(BufReader::new(file)))?;
+    Ok(page)
+}
+
+/// 把URL转换成可以作为文件名的形式，如 cq.ke.com_ershoufang_dazhulin_pg2
+fn file_slug(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let slug: String = without_scheme
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    slug.trim_matches('_').chars().take(150).collect()
+}
@@ -0,0 +1,151 @@
+use crate::crawl::FailedPage;
+use crate::pagination::PageInfo;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// 写入状态文件的爬取进度
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CrawlState {
+    /// 已完成的页地址及其分页信息，恢复时据此跳过这些页并重新确定页数和拆分方式
+    completed: BTreeMap<String, PageInfo>,
+    /// 上一次运行中重试后仍然失败的页，恢复时会重新爬取
+    failed: Vec<FailedPage>,
+}
+
+/// 单个任务的断点：`<stem>.state.json` 记录已完成和失败的页，
+/// `<stem>.partial.jsonl` 逐页追加已解析的记录，进程中途退出也不会丢失已爬取的数据
+pub struct Checkpoint {
+    state_path: PathBuf,
+    partial_path: PathBuf,
+    state: CrawlState,
+    partial: File,
+}
+
+impl Checkpoint {
+    /// 在 `output_dir` 下为任务 `stem` 创建断点
+    ///
+    /// `resume` 为 true 时读取已有的进度；否则清空之前的进度，从头开始。
+    pub fn open(output_dir: &Path, stem: &str, resume: bool) -> Result<Self> {
+        let state_path = output_dir.join(format!("{}.state.json", stem));
+        let partial_path = output_dir.join(format!("{}.partial.jsonl", stem));
+
+        let state = if resume && state_path.exists() {
+            let content = fs::read_to_string(&state_path)
+                .with_context(|| format!("读取断点文件失败: {}", state_path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("解析断点文件失败: {}", state_path.display()))?
+        } else {
+            CrawlState::default()
+        };
+
+        if resume && partial_path.exists() {
+            drop_torn_line(&partial_path)?;
+        }
+        let partial = OpenOptions::new()
+            .create(true)
+            .append(resume)
+            .write(true)
+            .truncate(!resume)
+            .open(&partial_path)
+            .with_context(|| format!("打开中间结果文件失败: {}", partial_path.display()))?;
+
+        Ok(Self { state_path, partial_path, state, partial })
+    }
+
+    /// 已完成的页数
+    pub fn completed_pages(&self) -> usize {
+        self.state.completed.len()
+    }
+
+    /// 已完成的页返回上一次记录的分页信息
+    pub fn completed(&self, url: &str) -> Option<PageInfo> {
+        self.state.completed.get(url).copied()
+    }
+
+    /// 读取上一次运行已经保存的记录
+    pub fn load_items<T: DeserializeOwned>(&self) -> Result<Vec<T>> {
+        let file = File::open(&self.partial_path)
+            .with_context(|| format!("读取中间结果文件失败: {}", self.partial_path.display()))?;
+        let mut items = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // 恢复时已截掉写了一半的最后一行，这里只会遇到被手工改坏的行
+            match serde_json::from_str(&line) {
+                Ok(item) => items.push(item),
+                Err(e) => println!("跳过无法解析的中间结果: {}", e),
+            }
+        }
+        Ok(items)
+    }
+
+    /// 记录一页成功：先追加该页的记录，再把页标记为完成
+    pub fn record_page<T: Serialize>(&mut self, url: &str, info: PageInfo, items: &[T]) -> Result<()> {
+        for item in items {
+            serde_json::to_writer(&mut self.partial, item)?;
+            self.partial.write_all(b"\n")?;
+        }
+        self.partial.flush()?;
+
+        self.state.failed.retain(|failed| failed.url != url);
+        self.state.completed.insert(url.to_string(), info);
+        self.save()
+    }
+
+    /// 记录一页在重试后仍然失败
+    pub fn record_failure(&mut self, failed: &FailedPage) -> Result<()> {
+        self.state.failed.retain(|f| f.url != failed.url);
+        self.state.failed.push(failed.clone());
+        self.save()
+    }
+
+    /// 任务完成后清理断点；仍有失败页时保留，以便 `--resume` 只重新爬取失败的页
+    pub fn finish(self, has_failures: bool) -> Result<()> {
+        if has_failures {
+            println!("有失败页，断点已保留，可以使用 --resume 重新爬取失败的页: {}", self.state_path.display());
+            return Ok(());
+        }
+
+        drop(self.partial);
+        for path in [&self.state_path, &self.partial_path] {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        // 先写临时文件再重命名，避免进程中途退出时留下不完整的状态文件
+        let tmp_path = self.state_path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(&self.state)?)?;
+        fs::rename(&tmp_path, &self.state_path)?;
+        Ok(())
+    }
+}
+
+/// 截掉中间结果文件末尾没有写完的一行
+///
+/// 进程在写入一条记录的中途退出时，文件以不完整的一行结尾，对应的页不会被标记为完成。
+/// 不截掉的话，恢复后追加的第一条记录会接在这一行后面，两条记录一起无法解析。
+fn drop_torn_line(path: &Path) -> Result<()> {
+    let content = fs::read(path).with_context(|| format!("读取中间结果文件失败: {}", path.display()))?;
+    if content.is_empty() || content.ends_with(b"\n") {
+        return Ok(());
+    }
+    let keep = content.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    println!("中间结果文件的最后一行不完整，已截掉 {} 字节: {}", content.len() - keep, path.display());
+    OpenOptions::new()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_len(keep as u64))
+        .with_context(|| format!("截断中间结果文件失败: {}", path.display()))?;
+    Ok(())
+}
@@ -1,110 +1,103 @@
 use anyhow::Result;
-use reqwest::Client;
 use scraper::{Html, Selector};
+use crate::checkpoint::Checkpoint;
+use crate::crawl::{crawl, CrawlReport, ListPage, ListScraper};
+use crate::error::ScrapeError;
+use crate::fetch::Fetcher;
+use crate::house::{parse_build_year, Floor};
+use crate::listing::ListingId;
+use crate::pagination::PageInfo;
+use crate::retry::RetryPolicy;
+use crate::search_url::SearchUrl;
+use crate::text::{parse_date, parse_integer, parse_number};
+use crate::xiaoqu::community_id;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChengjiaoData {
+    /// 成交编号，取自详情链接或列表项的 data 属性
+    pub id: Option<ListingId>,
     pub title: String,
+    /// 小区名称，取自标题开头，如 "金科天籁城 3室2厅 89.5平米" 中的 "金科天籁城"
+    pub community: String,
+    /// 小区编号，取自列表项中的小区链接，没有链接时由成交详情页补全
+    pub community_id: Option<String>,
+    /// 成交详情页链接
+    pub url: String,
+    /// 成交价（万元）
+    pub deal_price: Option<f64>,
+    /// 挂牌价（万元）
+    pub list_price: Option<f64>,
+    /// 成交单价（元/平米）
+    pub unit_price: Option<f64>,
+    pub deal_date: Option<NaiveDate>,
+    pub is_renovated: String,
+    /// 成交周期（天）
+    pub deal_cycle_days: Option<u32>,
+    /// 卖家让价（万元），挂牌价减成交价
+    pub discount: Option<f64>,
+    /// 让价占挂牌价的百分比，保留两位小数
+    pub discount_percent: Option<f64>,
+    /// 朝向，如 "南 北"
+    pub orientation: String,
+    pub floor: Floor,
+    pub build_year: Option<u32>,
+    /// 建筑类型，如 "板楼"、"塔楼"、"板塔结合"
+    pub building_type: String,
+    /// 页面上的原始文本，用于核对解析结果
+    pub raw: ChengjiaoRaw,
+}
+
+/// 成交记录各字段在页面上的原始文本，没有找到时为空
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChengjiaoRaw {
     pub deal_price: String,
     pub list_price: String,
+    pub unit_price: String,
     pub deal_date: String,
-    pub is_renovated: String,
     pub deal_cycle: String,
+    /// 朝向和装修，如 "南 北 | 精装"
+    pub house_info: String,
+    /// 楼层、建成年份和建筑类型，如 "中楼层(共18层) 2015年建板楼"
+    pub position_info: String,
 }
 
 pub struct ChengjiaoScraper {
-    client: Client,
-    cookies: String,
+    fetcher: Fetcher,
+    retry_policy: RetryPolicy,
+    concurrency: usize,
 }
 
 impl ChengjiaoScraper {
-    pub fn new(cookies: String) -> Self {
-        let client = Client::builder()
-            .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-            .build()
-            .unwrap();
-        
-        Self { client, cookies }
+    pub fn new(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher,
+            retry_policy: RetryPolicy::default(),
+            concurrency: 1,
+        }
     }
 
-    pub async fn scrape_chengjiao_page(&self, url: &str) -> Result<Vec<ChengjiaoData>> {
-        let response = self.client
-            .get(url)
-            .header("Cookie", &self.cookies)
-            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
-            .header("Accept-Language", "zh-CN,zh;q=0.9,en;q=0.8")
-            .header("Accept-Encoding", "gzip, deflate, br")
-            .header("Connection", "keep-alive")
-            .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-            .header("Host", "cq.ke.com")
-            .header("Referer", "https://cq.ke.com/")
-            .send()
-            .await?;
-
-        // 检查响应状态
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("HTTP请求失败: {}", response.status()));
-        }
+    /// 设置单页失败时的重试策略
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
 
-        // 检查内容类型
-        let content_type = response.headers()
-            .get("content-type")
-            .and_then(|ct| ct.to_str().ok())
-            .unwrap_or("unknown");
-        
-        println!("响应状态: {}", response.status());
-        println!("内容类型: {}", content_type);
+    /// 设置同时在途的列表页请求数，实际请求速率仍受 Fetcher 的限速约束
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
 
-        // 获取原始字节数据
-        let bytes = response.bytes().await?;
-        
-        // 检查是否被gzip压缩
-        let html_content = if bytes.len() > 2 && bytes[0] == 0x1f && bytes[1] == 0x8b {
-            // 这是gzip压缩的数据
-            println!("检测到gzip压缩数据，正在解压...");
-            use std::io::Read;
-            use flate2::read::GzDecoder;
-            
-            let mut decoder = GzDecoder::new(&bytes[..]);
-            let mut decompressed = String::new();
-            decoder.read_to_string(&mut decompressed)
-                .map_err(|e| anyhow::anyhow!("解压失败: {}", e))?;
-            decompressed
-        } else {
-            // 尝试直接解码为UTF-8
-            String::from_utf8(bytes.to_vec())
-                .map_err(|e| anyhow::anyhow!("UTF-8解码失败: {}", e))?
-        };
-        
-        // 以UTF-8编码输出HTML内容
-        println!("=== HTML内容 (UTF-8编码) ===");
-        println!("HTML内容长度: {} 字符", html_content.len());
-        
-        // 安全地显示前100个字符
-        let preview_start = html_content.chars().take(100).collect::<String>();
-        println!("HTML内容前100个字符: {}", preview_start);
-        
-        // 安全地显示后100个字符
-        let preview_end = html_content.chars().rev().take(100).collect::<Vec<char>>();
-        let preview_end: String = preview_end.into_iter().rev().collect();
-        println!("HTML内容后100个字符: {}", preview_end);
-        
-        // 检查是否包含HTML标签
-        if html_content.contains("<html") || html_content.contains("<!DOCTYPE") {
-            println!("检测到HTML内容");
-        } else {
-            println!("未检测到标准HTML内容，可能是压缩或编码问题");
-        }
-        
-        println!("=== HTML内容结束 ===");
-        
-        let document = Html::parse_document(&html_content);
+    pub async fn scrape_chengjiao_page(&self, url: &str) -> Result<ListPage<ChengjiaoData>, ScrapeError> {
+        let html_content = self.fetcher.fetch(url).await?;
+        self.parse_page(&html_content).non_empty()
+    }
 
-        // 检查是否遇到人机验证
-        if html_content.contains("人机验证") || html_content.contains("CAPTCHA") {
-            return Err(anyhow::anyhow!("遇到人机验证，请稍后重试"));
-        }
+    /// 解析列表页 HTML，不发起任何网络请求
+    pub fn parse_page(&self, html_content: &str) -> ListPage<ChengjiaoData> {
+        let document = Html::parse_document(html_content);
 
         // 选择成交列表项 - 尝试多种可能的选择器
         let list_selectors = vec![
@@ -130,8 +123,9 @@ impl ChengjiaoScraper {
                     // 重新选择，因为count()消耗了迭代器
                     let items = document.select(&list_selector);
                     for item in items {
-                        if let Ok(chengjiao_data) = self.parse_list_item(&item) {
-                            results.push(chengjiao_data);
+                        match self.parse_list_item(&item) {
+                            Ok(chengjiao_data) => results.push(chengjiao_data),
+                            Err(e) => println!("跳过列表项: {}", e),
                         }
                     }
                     break; // 找到有效选择器后退出
@@ -139,12 +133,12 @@ impl ChengjiaoScraper {
             }
         }
 
-        Ok(results)
+        ListPage { items: results, info: PageInfo::parse(&document) }
     }
 
-    fn parse_list_item(&self, item: &scraper::ElementRef) -> Result<ChengjiaoData> {
+    fn parse_list_item(&self, item: &scraper::ElementRef) -> Result<ChengjiaoData, ScrapeError> {
         // 尝试多种选择器来提取数据
-        let title_selectors = vec![
+        let title_selectors = [
             ".title a",
             "a[class*='title']",
             "h3 a",
@@ -153,7 +147,7 @@ impl ChengjiaoScraper {
             "a"
         ];
         
-        let price_selectors = vec![
+        let price_selectors = [
             ".totalPrice .number",
             ".totalPrice",
             ".price .number",
@@ -162,105 +156,52 @@ impl ChengjiaoScraper {
             "span[class*='total']"
         ];
         
-        let list_price_selectors = vec![
+        let unit_price_selectors = [
             ".unitPrice .number",
             ".unitPrice",
-            ".listPrice",
-            "span[class*='unit']",
-            "span[class*='list']"
+            "span[class*='unit']"
         ];
         
-        let date_selectors = vec![
+        let date_selectors = [
             ".dealDate",
             ".date",
             "span[class*='date']",
             "span[class*='deal']"
         ];
         
-        let info_selectors = vec![
+        let info_selectors = [
             ".houseInfo",
             ".info",
             "span[class*='info']",
             "span[class*='house']"
         ];
-        
-        let cycle_selectors = vec![
-            ".dealCycleInfo",
-            ".cycle",
-            "span[class*='cycle']",
-            "span[class*='deal']"
-        ];
-
-        // 提取标题
-        let mut title = "未知".to_string();
-        for selector_str in &title_selectors {
-            if let Ok(selector) = Selector::parse(selector_str) {
-                if let Some(el) = item.select(&selector).next() {
-                    let text = el.text().collect::<String>().trim().to_string();
-                    if !text.is_empty() && text != "未知" {
-                        title = text;
-                        break;
-                    }
-                }
-            }
-        }
 
-        // 提取成交价
-        let mut deal_price = "未知".to_string();
-        for selector_str in &price_selectors {
-            if let Ok(selector) = Selector::parse(selector_str) {
-                if let Some(el) = item.select(&selector).next() {
-                    let text = el.text().collect::<String>().trim().to_string();
-                    if !text.is_empty() && text != "未知" {
-                        deal_price = text;
-                        break;
-                    }
-                }
-            }
-        }
-
-        // 提取挂牌价
-        let mut list_price = "未知".to_string();
-        for selector_str in &list_price_selectors {
-            if let Ok(selector) = Selector::parse(selector_str) {
-                if let Some(el) = item.select(&selector).next() {
-                    let text = el.text().collect::<String>().trim().to_string();
-                    if !text.is_empty() && text != "未知" {
-                        list_price = text;
-                        break;
-                    }
-                }
-            }
-        }
+        let title = select_text(item, &title_selectors);
+        let url = select_href(item, &[".title a", "a[href*='chengjiao']"]).unwrap_or_default();
+        // 挂牌价和成交周期都在 .dealCycleeInfo 里（页面上的类名就是这么拼的）
+        let (list_price, deal_cycle) = deal_cycle_info(item);
+        let raw = ChengjiaoRaw {
+            deal_price: select_text(item, &price_selectors).unwrap_or_default(),
+            list_price,
+            unit_price: select_text(item, &unit_price_selectors).unwrap_or_default(),
+            deal_date: select_text(item, &date_selectors).unwrap_or_default(),
+            deal_cycle,
+            house_info: select_text(item, &info_selectors).unwrap_or_default(),
+            position_info: select_text(item, &[".positionInfo"]).unwrap_or_default(),
+        };
 
-        // 提取成交日期
-        let mut deal_date = "未知".to_string();
-        for selector_str in &date_selectors {
-            if let Ok(selector) = Selector::parse(selector_str) {
-                if let Some(el) = item.select(&selector).next() {
-                    let text = el.text().collect::<String>().trim().to_string();
-                    if !text.is_empty() && text != "未知" {
-                        deal_date = text;
-                        break;
-                    }
-                }
-            }
+        // 如果标题和价格都没有，说明这不是一条成交记录
+        if title.is_none() && raw.deal_price.is_empty() && raw.list_price.is_empty() && raw.unit_price.is_empty() {
+            let full_text = item.text().collect::<String>();
+            println!("调试 - 整个元素的文本: {}", full_text);
+            return Err(ScrapeError::Parse {
+                field: "title",
+                reason: "列表项中没有可识别的标题和价格".to_string(),
+            });
         }
 
         // 提取装修信息
-        let mut house_info = "未知".to_string();
-        for selector_str in &info_selectors {
-            if let Ok(selector) = Selector::parse(selector_str) {
-                if let Some(el) = item.select(&selector).next() {
-                    let text = el.text().collect::<String>().trim().to_string();
-                    if !text.is_empty() && text != "未知" {
-                        house_info = text;
-                        break;
-                    }
-                }
-            }
-        }
-        
+        let house_info = &raw.house_info;
         let is_renovated = if house_info.contains("精装") {
             "精装".to_string()
         } else if house_info.contains("简装") {
@@ -271,62 +212,136 @@ impl ChengjiaoScraper {
             "未知".to_string()
         };
 
-        // 提取成交周期
-        let mut deal_cycle = "未知".to_string();
-        for selector_str in &cycle_selectors {
-            if let Ok(selector) = Selector::parse(selector_str) {
-                if let Some(el) = item.select(&selector).next() {
-                    let text = el.text().collect::<String>().trim().to_string();
-                    if !text.is_empty() && text != "未知" {
-                        deal_cycle = text;
-                        break;
-                    }
-                }
-            }
-        }
+        // 朝向是 houseInfo 中只由方位字组成的一段
+        let orientation = house_info
+            .split('|')
+            .map(str::trim)
+            .find(|part| !part.is_empty() && part.chars().all(|c| "东南西北 ".contains(c)))
+            .unwrap_or("未知")
+            .to_string();
 
-        // 如果所有字段都是"未知"，尝试从整个元素的文本中提取信息
-        if title == "未知" && deal_price == "未知" && list_price == "未知" {
-            let full_text = item.text().collect::<String>();
-            println!("调试 - 整个元素的文本: {}", full_text);
-        }
+        let building_type = ["板塔结合", "板楼", "塔楼", "平房"]
+            .into_iter()
+            .find(|kind| raw.position_info.contains(kind))
+            .unwrap_or("未知")
+            .to_string();
+
+        let deal_price = parse_number(&raw.deal_price);
+        let list_price = parse_number(&raw.list_price);
+        let discount = deal_price.zip(list_price).map(|(deal, list)| round2(list - deal));
+        let discount_percent = discount
+            .zip(list_price)
+            .filter(|(_, list)| *list > 0.0)
+            .map(|(discount, list)| round2(discount / list * 100.0));
 
         Ok(ChengjiaoData {
-            title,
+            id: ListingId::from_element(item),
+            community: title.as_deref().and_then(community_from_title).unwrap_or_else(|| "未知".to_string()),
+            title: title.unwrap_or_else(|| "未知".to_string()),
+            community_id: select_href(item, &["a[href*='/xiaoqu/']"]).and_then(|href| community_id(&href)),
+            url,
             deal_price,
             list_price,
-            deal_date,
+            unit_price: parse_number(&raw.unit_price),
+            deal_date: parse_date(&raw.deal_date),
             is_renovated,
-            deal_cycle,
+            deal_cycle_days: parse_deal_cycle(&raw.deal_cycle),
+            discount,
+            discount_percent,
+            orientation,
+            floor: Floor::parse(&raw.position_info),
+            build_year: parse_build_year(&raw.position_info),
+            building_type,
+            raw,
         })
     }
 
-    pub async fn scrape_multiple_pages(&self, base_url: &str, start_page: u32, end_page: u32) -> Result<Vec<ChengjiaoData>> {
-        let mut all_results = Vec::new();
-        
-        for page in start_page..=end_page {
-            let url = base_url.replace("pg1", &format!("pg{}", page));
-            println!("正在爬取第 {} 页: {}", page, url);
-            
-            match self.scrape_chengjiao_page(&url).await {
-                Ok(results) => {
-                    println!("第 {} 页成功获取 {} 条数据", page, results.len());
-                    all_results.extend(results);
-                }
-                Err(e) => {
-                    println!("第 {} 页爬取失败: {}", page, e);
-                    // 如果遇到人机验证，暂停一下再继续
-                    if e.to_string().contains("人机验证") {
-                        println!("遇到人机验证，等待5秒后继续...");
-                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    }
+    /// 爬取 `start_page` 到 `end_page` 的列表页，`end_page` 为 None 时根据第一页的分页信息自动确定页数
+    ///
+    /// 每页结果写入 `checkpoint`，已在断点中完成的页会被跳过。
+    pub async fn scrape_multiple_pages(&self, search: &SearchUrl, start_page: u32, end_page: Option<u32>, checkpoint: &mut Checkpoint) -> Result<CrawlReport<ChengjiaoData>> {
+        crawl(self, search, start_page, end_page, checkpoint).await
+    }
+}
+
+impl ListScraper for ChengjiaoScraper {
+    type Item = ChengjiaoData;
+
+    async fn scrape_page(&self, url: &str) -> Result<ListPage<ChengjiaoData>, ScrapeError> {
+        self.scrape_chengjiao_page(url).await
+    }
+
+    fn item_key(item: &ChengjiaoData) -> String {
+        match &item.id {
+            Some(id) => id.to_string(),
+            None => format!("{}|{}|{}", item.title, item.raw.deal_date, item.raw.deal_price),
+        }
+    }
+
+    fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+}
+
+/// 依次尝试 `selectors`，返回第一个非空的文本
+fn select_text(item: &scraper::ElementRef, selectors: &[&str]) -> Option<String> {
+    for selector_str in selectors {
+        if let Ok(selector) = Selector::parse(selector_str) {
+            if let Some(el) = item.select(&selector).next() {
+                let text = el.text().collect::<String>().trim().to_string();
+                if !text.is_empty() {
+                    return Some(text);
                 }
             }
-            
-            // 添加延迟避免被封
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
         }
-        
-        Ok(all_results)
+    }
+    None
+}
+
+/// 依次尝试 `selectors`，返回第一个带 href 的链接
+fn select_href(item: &scraper::ElementRef, selectors: &[&str]) -> Option<String> {
+    selectors
+        .iter()
+        .filter_map(|selector_str| Selector::parse(selector_str).ok())
+        .find_map(|selector| {
+            item.select(&selector)
+                .find_map(|el| el.value().attr("href"))
+                .filter(|href| !href.is_empty())
+                .map(str::to_string)
+        })
+}
+
+/// `.dealCycleeInfo` 中的 "挂牌125万" 和 "成交周期46天"，返回 (挂牌价, 成交周期) 的原始文本
+fn deal_cycle_info(item: &scraper::ElementRef) -> (String, String) {
+    let span_selector = Selector::parse(".dealCycleeInfo .dealCycleTxt span").unwrap();
+    let spans: Vec<String> = item.select(&span_selector)
+        .map(|el| el.text().collect::<String>().trim().to_string())
+        .collect();
+    let find = |keyword: &str| spans.iter().find(|s| s.contains(keyword)).cloned().unwrap_or_default();
+    (find("挂牌"), find("周期"))
+}
+
+/// 成交标题的格式是 "小区 户型 面积"，第二段是户型时第一段就是小区名
+fn community_from_title(title: &str) -> Option<String> {
+    let mut parts = title.split_whitespace();
+    let community = parts.next()?;
+    parts.next().filter(|layout| layout.contains('室')).map(|_| community.to_string())
+}
+
+/// 保留两位小数，避免 125.0 - 118.3 这样的浮点误差出现在输出里
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// 成交周期天数，文本可能和挂牌价连在一起，如 "挂牌88万成交周期21天"
+fn parse_deal_cycle(text: &str) -> Option<u32> {
+    match text.find("周期") {
+        Some(pos) => parse_integer(&text[pos..]),
+        None if text.ends_with('天') => parse_integer(text),
+        None => None,
     }
 }
@@ -0,0 +1,197 @@
+use crate::chengjiao::ChengjiaoData;
+use crate::detail::{attributes, canonical_url, check_id, crawl_details, known, text_without_children, yes_no, DetailOptions, DetailRecord, DetailReport, DetailScraper};
+use crate::error::ScrapeError;
+use crate::fetch::Fetcher;
+use crate::listing::ListingId;
+use crate::text::{parse_date, parse_integer, parse_number};
+use crate::xiaoqu::community_id;
+use anyhow::Result;
+use chrono::NaiveDate;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 成交详情页上的信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChengjiaoDetail {
+    /// 成交编号，取自 "交易属性" 中的链家编号，没有时取页面的规范地址
+    pub deal_id: Option<ListingId>,
+    /// 小区编号，取自页面上的小区链接
+    pub community_id: Option<String>,
+    /// 挂牌时间
+    pub listed_on: Option<NaiveDate>,
+    /// 调价次数
+    pub price_adjustments: Option<u32>,
+    /// 带看次数
+    pub showings: Option<u32>,
+    /// 关注人数
+    pub followers: Option<u32>,
+    /// 浏览次数
+    pub views: Option<u32>,
+    /// 交易权属，如 "商品房"
+    pub ownership: Option<String>,
+    /// 产权年限（年）
+    pub property_right_years: Option<u32>,
+    /// 供暖方式
+    pub heating: Option<String>,
+    /// 梯户比例，如 "两梯四户"
+    pub elevator_ratio: Option<String>,
+    /// 是否配备电梯
+    pub has_elevator: Option<bool>,
+    /// "基本属性" 中的全部条目，页面上显示 "暂无数据" 的条目也保留
+    pub base_attributes: BTreeMap<String, String>,
+    /// "交易属性" 中的全部条目
+    pub transaction_attributes: BTreeMap<String, String>,
+    /// 该房源的历次成交，最近的在前
+    pub history: Vec<DealHistory>,
+}
+
+/// 详情页 "历史成交记录" 中的一次成交
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DealHistory {
+    /// 成交价（万元）
+    pub deal_price: Option<f64>,
+    /// 成交单价（元/平米）
+    pub unit_price: Option<f64>,
+    /// 成交年月，如 "2024-05"
+    pub deal_month: String,
+}
+
+/// 列表记录与成交详情合并后的记录
+pub type ChengjiaoRecord = DetailRecord<ChengjiaoData, ChengjiaoDetail>;
+
+pub struct ChengjiaoDetailScraper {
+    fetcher: Fetcher,
+    options: DetailOptions,
+}
+
+impl ChengjiaoDetailScraper {
+    pub fn new(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher,
+            options: DetailOptions::default(),
+        }
+    }
+
+    pub async fn scrape_chengjiao_detail(&self, url: &str) -> Result<ChengjiaoDetail, ScrapeError> {
+        let html_content = self.fetcher.fetch(url).await?;
+        let detail = self.parse_detail(&html_content);
+        // 成交信息和属性都没有，多半是被拦截后返回的空页面
+        if detail.base_attributes.is_empty() && detail.transaction_attributes.is_empty() && detail.price_adjustments.is_none() {
+            return Err(ScrapeError::EmptyPage);
+        }
+        Ok(detail)
+    }
+
+    /// 解析详情页 HTML，不发起任何网络请求
+    pub fn parse_detail(&self, html_content: &str) -> ChengjiaoDetail {
+        let document = Html::parse_document(html_content);
+
+        // 成交信息：<span><label>2</label>调价（次）</span>
+        let msg_selector = Selector::parse(".msg span").unwrap();
+        let label_selector = Selector::parse("label").unwrap();
+        let mut counts: BTreeMap<String, String> = BTreeMap::new();
+        for span in document.select(&msg_selector) {
+            let value = span.select(&label_selector)
+                .next()
+                .map(|el| el.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+            let name = text_without_children(&span);
+            counts.insert(name, value);
+        }
+        let count = |keyword: &str| {
+            counts.iter()
+                .find(|(name, _)| name.contains(keyword))
+                .and_then(|(_, value)| parse_integer(value))
+        };
+
+        let base_attributes = attributes(&document, ".introContent .base li");
+        let transaction_attributes = attributes(&document, ".introContent .transaction li");
+        let base = |name: &str| known(base_attributes.get(name));
+        let transaction = |name: &str| known(transaction_attributes.get(name));
+
+        let community_selector = Selector::parse("a[href*='/xiaoqu/']").unwrap();
+        let community_id = document.select(&community_selector)
+            .find_map(|el| el.value().attr("href").and_then(community_id));
+
+        let deal_id = transaction("链家编号")
+            .or_else(|| transaction("贝壳编号"))
+            .and_then(|text| ListingId::parse(&text))
+            .or_else(|| canonical_url(&document).and_then(|url| ListingId::from_url(&url)));
+
+        ChengjiaoDetail {
+            deal_id,
+            community_id,
+            listed_on: transaction("挂牌时间").and_then(|text| parse_date(&text)),
+            price_adjustments: count("调价"),
+            showings: count("带看"),
+            followers: count("关注"),
+            views: count("浏览"),
+            ownership: transaction("交易权属"),
+            property_right_years: base("产权年限").and_then(|text| parse_integer(&text)),
+            heating: base("供暖方式"),
+            elevator_ratio: base("梯户比例"),
+            has_elevator: base("配备电梯").and_then(|text| yes_no(&text)),
+            history: history(&document),
+            base_attributes,
+            transaction_attributes,
+        }
+    }
+}
+
+impl DetailScraper for ChengjiaoDetailScraper {
+    type Summary = ChengjiaoData;
+    type Detail = ChengjiaoDetail;
+
+    fn options(&self) -> &DetailOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut DetailOptions {
+        &mut self.options
+    }
+
+    fn summary_url(summary: &ChengjiaoData) -> &str {
+        &summary.url
+    }
+
+    async fn scrape_detail(&self, url: &str) -> Result<ChengjiaoDetail, ScrapeError> {
+        self.scrape_chengjiao_detail(url).await
+    }
+
+    /// 详情页上的成交编号与列表记录不一致时（如被重定向到了其他成交记录）不合并
+    fn check_detail(&self, summary: &ChengjiaoData, detail: &ChengjiaoDetail) -> Result<(), ScrapeError> {
+        check_id("deal_id", "成交编号", summary.id.as_ref(), detail.deal_id.as_ref())
+    }
+
+    /// 列表项中没有小区链接时，用详情页上的小区编号补全列表记录，以便按编号关联到小区表。
+    /// 编号不一致的详情页已被 [`check_detail`](Self::check_detail) 拒绝，不会用来补全。
+    async fn scrape_details(&self, items: Vec<ChengjiaoData>) -> Result<DetailReport<ChengjiaoData, ChengjiaoDetail>> {
+        let mut report = crawl_details(self, items).await?;
+        for record in &mut report.records {
+            if record.summary.community_id.is_none() {
+                record.summary.community_id = record.detail.as_ref().and_then(|detail| detail.community_id.clone());
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// 历史成交记录：<span class="record_price">118万</span><p class="record_detail">单价13185元/平,2024-05</p>
+fn history(document: &Html) -> Vec<DealHistory> {
+    let item_selector = Selector::parse(".chengjiao_record .record_list li").unwrap();
+    let price_selector = Selector::parse(".record_price").unwrap();
+    let detail_selector = Selector::parse(".record_detail").unwrap();
+    document.select(&item_selector)
+        .map(|li| {
+            let price = li.select(&price_selector).next().map(|el| el.text().collect::<String>()).unwrap_or_default();
+            let detail = li.select(&detail_selector).next().map(|el| el.text().collect::<String>()).unwrap_or_default();
+            let (unit_price, deal_month) = detail.split_once([',', '，']).unwrap_or((detail.as_str(), ""));
+            DealHistory {
+                deal_price: parse_number(&price),
+                unit_price: parse_number(unit_price),
+                deal_month: deal_month.trim().to_string(),
+            }
+        })
+        .collect()
+}
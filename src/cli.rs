@@ -0,0 +1,275 @@
+use crate::config::JobConfig;
+use crate::filter::{Decoration, Range, SearchFilter};
+use crate::output::OutputFormat;
+use crate::ratelimit::RateLimit;
+use crate::retry::RetryPolicy;
+use crate::search_url::{FilterSegment, ListingKind, SearchUrl};
+use anyhow::Result;
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(name = "fastToMortgageSlave", version, about = "贝壳网成交/在售数据爬虫")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 爬取成交数据
+    Chengjiao(CrawlArgs),
+    /// 爬取在售数据
+    Ershoufang(CrawlArgs),
+    /// 爬取小区数据
+    Xiaoqu(CrawlArgs),
+    /// 爬取租房数据
+    Zufang(CrawlArgs),
+    /// 爬取新房楼盘数据
+    Loupan(CrawlArgs),
+    /// 先爬取成交数据，再爬取在售数据
+    All(CrawlArgs),
+    /// 按配置文件依次执行所有任务
+    Run(RunArgs),
+    /// 离线重新解析归档中的页面，不发起网络请求
+    Reparse(ReparseArgs),
+    /// 用已保存的小区、在售、成交和租房数据建立小区表，不发起网络请求
+    Communities(CommunitiesArgs),
+    /// 从在售任务累积的价格历史中查询降价房源，不发起网络请求
+    PriceCuts(PriceCutsArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// 任务配置文件，支持 .toml 和 .yaml/.yml
+    #[arg(long, short)]
+    pub config: PathBuf,
+
+    /// 贝壳网Cookie，优先于配置文件中的 cookie
+    #[arg(long, env = "KE_COOKIE", hide_env_values = true)]
+    pub cookie: Option<String>,
+
+    /// 从上一次中断的位置继续，跳过断点中已完成的页
+    #[arg(long)]
+    pub resume: bool,
+
+    /// 原始页面归档目录，优先于配置文件中的 archive_dir
+    #[arg(long)]
+    pub archive_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ReparseArgs {
+    /// 爬取时通过 --archive-dir 保存的归档目录
+    #[arg(long)]
+    pub archive_dir: PathBuf,
+
+    /// 输出目录
+    #[arg(long, default_value = ".")]
+    pub output_dir: PathBuf,
+
+    /// 输出格式，多个格式用逗号分隔
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [OutputFormat::Json, OutputFormat::Csv])]
+    pub format: Vec<OutputFormat>,
+}
+
+#[derive(Debug, Args)]
+pub struct CommunitiesArgs {
+    /// xiaoqu 任务输出的小区数据，`<name>_data.json` 或带详情的 `<name>_detail.json`
+    #[arg(long)]
+    pub xiaoqu: PathBuf,
+
+    /// 要关联的在售数据 JSON，可以指定多次
+    #[arg(long)]
+    pub ershoufang: Vec<PathBuf>,
+
+    /// 要关联的成交数据 JSON，可以指定多次
+    #[arg(long)]
+    pub chengjiao: Vec<PathBuf>,
+
+    /// 要关联的租房数据 JSON，可以指定多次，用于计算租金回报率
+    #[arg(long)]
+    pub zufang: Vec<PathBuf>,
+
+    /// 输出文件名前缀
+    #[arg(long, default_value = "communities")]
+    pub name: String,
+
+    /// 输出目录
+    #[arg(long, default_value = ".")]
+    pub output_dir: PathBuf,
+
+    /// 输出格式，多个格式用逗号分隔
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [OutputFormat::Json, OutputFormat::Csv])]
+    pub format: Vec<OutputFormat>,
+}
+
+#[derive(Debug, Args)]
+pub struct PriceCutsArgs {
+    /// 在售任务保存的价格历史，如 `ershoufang_history.json`
+    #[arg(long)]
+    pub history: PathBuf,
+
+    /// 只列出降价次数不少于该值的房源，0 表示列出全部房源
+    #[arg(long, default_value_t = 1)]
+    pub min_cuts: usize,
+
+    /// 输出文件名前缀
+    #[arg(long, default_value = "price_cuts")]
+    pub name: String,
+
+    /// 输出目录
+    #[arg(long, default_value = ".")]
+    pub output_dir: PathBuf,
+
+    /// 输出格式，多个格式用逗号分隔
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [OutputFormat::Json, OutputFormat::Csv])]
+    pub format: Vec<OutputFormat>,
+}
+
+#[derive(Debug, Args)]
+pub struct CrawlArgs {
+    /// 贝壳网Cookie，也可以通过环境变量 KE_COOKIE 提供
+    #[arg(long, env = "KE_COOKIE", default_value = "", hide_env_values = true)]
+    pub cookie: String,
+
+    /// 城市子域名，如 cq 对应 https://cq.ke.com
+    #[arg(long, default_value = "cq")]
+    pub city: String,
+
+    /// 区域或商圈的拼音标识，如 dazhulin
+    #[arg(long, default_value = "dazhulin")]
+    pub district: String,
+
+    /// 追加在页码后的筛选段，如 de1（精装）、l3（三室），多个用逗号分隔
+    #[arg(long = "filter", value_delimiter = ',')]
+    pub filters: Vec<FilterSegment>,
+
+    /// 居室数，多个用逗号分隔，如 2,3
+    #[arg(long, value_delimiter = ',')]
+    pub rooms: Vec<u32>,
+
+    /// 建筑面积区间（平米），如 80-100、80-、-100
+    #[arg(long, allow_hyphen_values = true)]
+    pub area: Option<Range>,
+
+    /// 总价区间（万），如 100-150、100-、-150
+    #[arg(long, allow_hyphen_values = true)]
+    pub price: Option<Range>,
+
+    /// 装修情况：精装、简装、毛坯，多个用逗号分隔
+    #[arg(long, value_delimiter = ',')]
+    pub decoration: Vec<Decoration>,
+
+    /// 替代 https://{city}.ke.com 的站点地址，如本地模拟服务器 http://127.0.0.1:8080
+    #[arg(long)]
+    pub base_url: Option<String>,
+
+    /// 直接使用已有的列表页地址，城市、区域和筛选条件都从地址中解析；
+    /// 镜像或本地模拟服务器的地址中没有城市，使用 --city，并向该站点发请求
+    #[arg(long, conflicts_with_all = ["district", "filters"])]
+    pub url: Option<String>,
+
+    /// 起始页
+    #[arg(long, default_value_t = 1)]
+    pub start_page: u32,
+
+    /// 结束页，不指定时根据结果总数自动确定页数
+    #[arg(long)]
+    pub end_page: Option<u32>,
+
+    /// 每页最多尝试次数（包含第一次请求）
+    #[arg(long, default_value_t = RetryPolicy::default().max_attempts)]
+    pub max_attempts: u32,
+
+    /// 每分钟最多请求数
+    #[arg(long, default_value_t = RateLimit::default().requests_per_minute)]
+    pub requests_per_minute: u32,
+
+    /// 两次请求之间的最短等待时间（毫秒）
+    #[arg(long, default_value_t = RateLimit::default().min_delay_ms)]
+    pub min_delay_ms: u64,
+
+    /// 两次请求之间的最长等待时间（毫秒）
+    #[arg(long, default_value_t = RateLimit::default().max_delay_ms)]
+    pub max_delay_ms: u64,
+
+    /// 同时在途的列表页请求数
+    #[arg(long, default_value_t = 1)]
+    pub concurrency: usize,
+
+    /// 列表爬完后继续爬取每条记录的详情页
+    #[arg(long)]
+    pub details: bool,
+
+    /// 输出目录
+    #[arg(long, default_value = ".")]
+    pub output_dir: PathBuf,
+
+    /// 输出格式，多个格式用逗号分隔
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [OutputFormat::Json, OutputFormat::Csv])]
+    pub format: Vec<OutputFormat>,
+
+    /// 从上一次中断的位置继续，跳过断点中已完成的页
+    #[arg(long)]
+    pub resume: bool,
+
+    /// 把抓取到的原始页面保存到该目录，之后可以用 reparse 子命令离线重新解析
+    #[arg(long)]
+    pub archive_dir: Option<PathBuf>,
+}
+
+impl CrawlArgs {
+    /// 把命令行参数转换成一个爬取任务，任务名沿用 chengjiao / ershoufang / xiaoqu / zufang / loupan 以保持原有输出文件名
+    pub fn job(&self, kind: ListingKind) -> Result<JobConfig> {
+        let (city, district, filter, base_url) = match &self.url {
+            Some(url) => {
+                let search = SearchUrl::parse(url)?;
+                let filter = SearchFilter::from_url(url)?;
+                (
+                    search.city.unwrap_or_else(|| self.city.clone()),
+                    search.area.unwrap_or_default(),
+                    filter,
+                    self.base_url.clone().or(search.base_url),
+                )
+            }
+            None => (self.city.clone(), self.district.clone(), SearchFilter::from_segments(&self.filters), self.base_url.clone()),
+        };
+
+        let filter = filter.merge(SearchFilter {
+            rooms: self.rooms.clone(),
+            area: self.area,
+            total_price: self.price,
+            decoration: self.decoration.clone(),
+            ..SearchFilter::default()
+        });
+
+        let job = JobConfig {
+            name: kind.path().to_string(),
+            kind,
+            city,
+            district,
+            base_url,
+            filter,
+            filters: Vec::new(),
+            start_page: self.start_page,
+            end_page: self.end_page,
+            rate_limit: RateLimit {
+                requests_per_minute: self.requests_per_minute,
+                min_delay_ms: self.min_delay_ms,
+                max_delay_ms: self.max_delay_ms,
+                ..RateLimit::default()
+            },
+            concurrency: self.concurrency,
+            retry: RetryPolicy {
+                max_attempts: self.max_attempts,
+                ..RetryPolicy::default()
+            },
+            details: self.details,
+            outputs: self.format.clone(),
+            output_dir: Some(self.output_dir.clone()),
+        };
+        job.validate()?;
+        Ok(job)
+    }
+}
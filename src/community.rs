@@ -0,0 +1,282 @@
+use crate::chengjiao::ChengjiaoData;
+use crate::ershoufang::ErshoufangData;
+use crate::listing::ListingId;
+use crate::output::{save_community_to_csv, save_outputs, OutputFormat};
+use crate::region::Region;
+use crate::xiaoqu_detail::XiaoquRecord;
+use crate::zufang::{RentalType, ZufangData};
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 小区表中的一个小区：小区列表和详情页上的信息，以及关联到该小区的在售房源、成交记录和出租房源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Community {
+    /// 小区编号，即 /xiaoqu/{id}/ 中的数字
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub region: Region,
+    /// 参考均价（元/平米），优先取详情页上的值
+    pub average_price: Option<f64>,
+    pub build_year: Option<u32>,
+    pub building_type: String,
+    pub building_count: Option<u32>,
+    pub household_count: Option<u32>,
+    pub developer: Option<String>,
+    pub property_company: Option<String>,
+    /// 物业费区间（元/平米/月）
+    pub property_fee_min: Option<f64>,
+    pub property_fee_max: Option<f64>,
+    /// 在售二手房套数
+    pub on_sale_count: Option<u32>,
+    /// 近90天成交套数
+    pub sold_count: Option<u32>,
+    /// 正在出租套数
+    pub renting_count: Option<u32>,
+    /// 关联到该小区的在售房源编号
+    pub listings: Vec<ListingId>,
+    /// 关联到该小区的成交编号
+    pub deals: Vec<ListingId>,
+    /// 关联在售房源的平均挂牌单价（元/平米）
+    pub listing_unit_price: Option<f64>,
+    /// 关联成交记录的平均成交单价（元/平米）
+    pub deal_unit_price: Option<f64>,
+    /// 关联到该小区的出租房源编号
+    pub rentals: Vec<ListingId>,
+    /// 关联整租房源的平均每平米月租金（元/平米/月），合租只有单间面积，不参与计算
+    pub rent_per_square_meter: Option<f64>,
+    /// 年租金回报率（%）：每平米月租金 × 12 ÷ 每平米售价，售价优先取在售均价，没有时取参考均价
+    pub rental_yield: Option<f64>,
+}
+
+impl Community {
+    fn from_record(id: String, record: XiaoquRecord) -> Self {
+        let XiaoquRecord { summary, detail } = record;
+        let detail = detail.as_ref();
+        Self {
+            id,
+            name: summary.name,
+            url: summary.url,
+            region: summary.region,
+            average_price: detail.and_then(|d| d.average_price).or(summary.average_price),
+            build_year: detail.and_then(|d| d.build_year).or(summary.build_year),
+            building_type: detail
+                .and_then(|d| d.building_type.clone())
+                .unwrap_or(summary.building_type),
+            building_count: detail.and_then(|d| d.building_count),
+            household_count: detail.and_then(|d| d.household_count),
+            developer: detail.and_then(|d| d.developer.clone()),
+            property_company: detail.and_then(|d| d.property_company.clone()),
+            property_fee_min: detail.and_then(|d| d.property_fee_min),
+            property_fee_max: detail.and_then(|d| d.property_fee_max),
+            on_sale_count: summary.on_sale_count,
+            sold_count: summary.sold_count,
+            renting_count: summary.renting_count,
+            listings: Vec::new(),
+            deals: Vec::new(),
+            listing_unit_price: None,
+            deal_unit_price: None,
+            rentals: Vec::new(),
+            rent_per_square_meter: None,
+            rental_yield: None,
+        }
+    }
+
+    /// 租金或售价变化后重新计算租金回报率
+    fn update_rental_yield(&mut self) {
+        let price = self.listing_unit_price.or(self.average_price).filter(|price| *price > 0.0);
+        self.rental_yield = match (self.rent_per_square_meter, price) {
+            (Some(rent), Some(price)) => Some(round2(rent * 12.0 / price * 100.0)),
+            _ => None,
+        };
+    }
+}
+
+/// 以小区编号为键的小区表
+///
+/// 在售、成交和出租记录优先按小区编号关联，没有编号或编号不在表中时按小区名称关联，
+/// 按名称关联的条数单独统计；同名小区不止一个时无法确定归属，计入未关联。
+#[derive(Debug, Default)]
+pub struct CommunityTable {
+    pub communities: BTreeMap<String, Community>,
+    /// 没有找到所属小区的在售房源数
+    pub unmatched_listings: usize,
+    /// 没有找到所属小区的成交记录数
+    pub unmatched_deals: usize,
+    /// 没有找到所属小区的出租房源数
+    pub unmatched_rentals: usize,
+    /// 没有可用的小区编号、按小区名称关联上的在售房源数
+    pub name_matched_listings: usize,
+    /// 没有可用的小区编号、按小区名称关联上的成交记录数
+    pub name_matched_deals: usize,
+    /// 没有可用的小区编号、按小区名称关联上的出租房源数
+    pub name_matched_rentals: usize,
+    /// 各小区已关联记录的单价（租金）之和与条数，用于计算平均值
+    listing_prices: HashMap<String, (f64, usize)>,
+    deal_prices: HashMap<String, (f64, usize)>,
+    rents: HashMap<String, (f64, usize)>,
+    /// 已关联过的记录编号，同一条记录在多个输入文件中出现时只关联一次
+    seen_listings: HashSet<ListingId>,
+    seen_deals: HashSet<ListingId>,
+    seen_rentals: HashSet<ListingId>,
+}
+
+impl CommunityTable {
+    /// 用小区列表（可带详情）建表，没有编号的小区无法作为主键，会被跳过
+    pub fn new(records: Vec<XiaoquRecord>) -> Self {
+        let mut table = Self::default();
+        for record in records {
+            match record.summary.id.clone() {
+                Some(id) => {
+                    table.communities.insert(id.clone(), Community::from_record(id, record));
+                }
+                None => println!("跳过没有编号的小区: {}", record.summary.name),
+            }
+        }
+        table
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Community> {
+        self.communities.get(id)
+    }
+
+    /// 按名称查找小区，同名小区不止一个时返回 None
+    pub fn find_by_name(&self, name: &str) -> Option<&Community> {
+        let mut matches = self.communities.values().filter(|community| community.name == name);
+        let community = matches.next()?;
+        matches.next().is_none().then_some(community)
+    }
+
+    /// 查找记录所属的小区：编号在表中时按编号，否则按名称，返回小区编号和是否按名称匹配
+    fn resolve(&self, community_id: Option<&str>, name: &str) -> Option<(String, bool)> {
+        if let Some(id) = community_id.filter(|id| self.communities.contains_key(*id)) {
+            return Some((id.to_string(), false));
+        }
+        self.find_by_name(name).map(|community| (community.id.clone(), true))
+    }
+
+    /// 把在售房源关联到所属小区，已关联过的房源编号会被跳过
+    pub fn link_listings(&mut self, items: &[ErshoufangData]) {
+        for item in items {
+            if !first_seen(&mut self.seen_listings, item.id.as_ref()) {
+                continue;
+            }
+            let Some((id, by_name)) = self.resolve(item.community_id.as_deref(), &item.community) else {
+                self.unmatched_listings += 1;
+                continue;
+            };
+            self.name_matched_listings += usize::from(by_name);
+            let community = self.communities.get_mut(&id).unwrap();
+            community.listings.extend(item.id.clone());
+            community.listing_unit_price = add_value(&mut self.listing_prices, &id, item.unit_price).map(f64::round);
+            community.update_rental_yield();
+        }
+    }
+
+    /// 把成交记录关联到所属小区，已关联过的成交编号会被跳过
+    pub fn link_deals(&mut self, items: &[ChengjiaoData]) {
+        for item in items {
+            if !first_seen(&mut self.seen_deals, item.id.as_ref()) {
+                continue;
+            }
+            let Some((id, by_name)) = self.resolve(item.community_id.as_deref(), &item.community) else {
+                self.unmatched_deals += 1;
+                continue;
+            };
+            self.name_matched_deals += usize::from(by_name);
+            let community = self.communities.get_mut(&id).unwrap();
+            community.deals.extend(item.id.clone());
+            community.deal_unit_price = add_value(&mut self.deal_prices, &id, item.unit_price).map(f64::round);
+        }
+    }
+
+    /// 把出租房源关联到所属小区，并用整租房源的租金计算租金回报率，已关联过的房源编号会被跳过
+    pub fn link_rentals(&mut self, items: &[ZufangData]) {
+        for item in items {
+            if !first_seen(&mut self.seen_rentals, item.id.as_ref()) {
+                continue;
+            }
+            let Some((id, by_name)) = self.resolve(item.community_id.as_deref(), &item.community) else {
+                self.unmatched_rentals += 1;
+                continue;
+            };
+            self.name_matched_rentals += usize::from(by_name);
+            let rent = item.rent_per_square_meter().filter(|_| item.rental_type == Some(RentalType::Whole));
+            let community = self.communities.get_mut(&id).unwrap();
+            community.rentals.extend(item.id.clone());
+            community.rent_per_square_meter = add_value(&mut self.rents, &id, rent).map(round2);
+            community.update_rental_yield();
+        }
+    }
+}
+
+/// 记录一个编号，第一次出现时返回 true；没有编号的记录无法判断是否重复，每次都算作新记录
+fn first_seen(seen: &mut HashSet<ListingId>, id: Option<&ListingId>) -> bool {
+    id.is_none_or(|id| seen.insert(id.clone()))
+}
+
+/// 累加一个单价或租金，返回该小区目前的平均值
+fn add_value(values: &mut HashMap<String, (f64, usize)>, id: &str, value: Option<f64>) -> Option<f64> {
+    let (sum, count) = values.entry(id.to_string()).or_insert((0.0, 0));
+    if let Some(value) = value {
+        *sum += value;
+        *count += 1;
+    }
+    (*count > 0).then(|| *sum / *count as f64)
+}
+
+/// 保留两位小数
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// 读取已保存的小区、在售、成交和租房 JSON，建立小区表并保存为 `<stem>.json` / `<stem>.csv`
+pub fn link_communities(
+    xiaoqu: &Path,
+    ershoufang: &[PathBuf],
+    chengjiao: &[PathBuf],
+    zufang: &[PathBuf],
+    output_dir: &Path,
+    stem: &str,
+    formats: &[OutputFormat],
+) -> Result<()> {
+    let mut table = CommunityTable::new(load_json(xiaoqu)?);
+    println!("小区表共 {} 个小区", table.communities.len());
+
+    for path in ershoufang {
+        let items: Vec<ErshoufangData> = load_json(path)?;
+        println!("关联在售数据 {} ({} 条)", path.display(), items.len());
+        table.link_listings(&items);
+    }
+    for path in chengjiao {
+        let items: Vec<ChengjiaoData> = load_json(path)?;
+        println!("关联成交数据 {} ({} 条)", path.display(), items.len());
+        table.link_deals(&items);
+    }
+    for path in zufang {
+        let items: Vec<ZufangData> = load_json(path)?;
+        println!("关联租房数据 {} ({} 条)", path.display(), items.len());
+        table.link_rentals(&items);
+    }
+
+    println!("\n=== 小区关联完成 ===");
+    println!("未关联的在售房源: {} 条，按名称关联: {} 条", table.unmatched_listings, table.name_matched_listings);
+    println!("未关联的成交记录: {} 条，按名称关联: {} 条", table.unmatched_deals, table.name_matched_deals);
+    if !zufang.is_empty() {
+        println!("未关联的出租房源: {} 条，按名称关联: {} 条", table.unmatched_rentals, table.name_matched_rentals);
+    }
+
+    let communities: Vec<&Community> = table.communities.values().collect();
+    save_outputs(&communities, output_dir, stem, formats, "小区表", save_community_to_csv)
+}
+
+/// 读取列表或详情输出的 JSON，带详情的记录同样可以按列表记录读取
+fn load_json<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("读取 {} 失败", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("解析 {} 失败", path.display()))
+}
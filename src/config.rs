@@ -0,0 +1,189 @@
+use crate::filter::SearchFilter;
+use crate::output::{default_formats, OutputFormat};
+use crate::ratelimit::RateLimit;
+use crate::retry::RetryPolicy;
+use crate::search_url::{FilterSegment, ListingKind, SearchUrl};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 爬取任务配置文件，支持 TOML 和 YAML 两种格式
+///
+/// ```toml
+/// output_dir = "output"
+///
+/// [[jobs]]
+/// name = "dazhulin_chengjiao"
+/// kind = "chengjiao"
+/// district = "dazhulin"
+/// filter = { rooms = [3], area = { min = 80, max = 100 }, decoration = ["精装"] }
+/// end_page = 5
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct CrawlConfig {
+    /// 贝壳网Cookie，命令行参数或 KE_COOKIE 环境变量优先
+    #[serde(default)]
+    pub cookie: String,
+    #[serde(default = "default_output_dir")]
+    pub output_dir: PathBuf,
+    /// 原始页面归档目录，不指定时不归档
+    pub archive_dir: Option<PathBuf>,
+    pub jobs: Vec<JobConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobConfig {
+    /// 任务名，同时作为输出文件名前缀
+    pub name: String,
+    pub kind: ListingKind,
+    /// 城市子域名，如 cq
+    #[serde(default = "default_city")]
+    pub city: String,
+    /// 区域或商圈的拼音标识，如 dazhulin
+    pub district: String,
+    /// 替代 `https://{city}.ke.com` 的站点地址，如镜像或本地模拟服务器
+    pub base_url: Option<String>,
+    /// 筛选条件，如 `filter = { rooms = [3], area = { min = 80, max = 100 }, decoration = ["精装"] }`
+    #[serde(default)]
+    pub filter: SearchFilter,
+    /// 追加在页码后的原始筛选段，如 de1（精装），会与 `filter` 合并
+    #[serde(default)]
+    pub filters: Vec<FilterSegment>,
+    #[serde(default = "default_start_page")]
+    pub start_page: u32,
+    /// 不指定时根据第一页的结果总数自动确定页数，超过100页时自动拆分查询
+    pub end_page: Option<u32>,
+    /// 对该站点的限速配置，同一 host 的请求共用一个令牌桶
+    #[serde(default)]
+    pub rate_limit: RateLimit,
+    /// 同时在途的列表页请求数，1 表示按顺序逐页爬取
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// 单页失败时的重试策略
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// 列表爬完后继续爬取每条记录的详情页，结果另存为 `<name>_detail.*`
+    #[serde(default)]
+    pub details: bool,
+    #[serde(default = "default_formats")]
+    pub outputs: Vec<OutputFormat>,
+    /// 不指定时使用配置文件顶层的 output_dir
+    pub output_dir: Option<PathBuf>,
+}
+
+impl CrawlConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("读取配置文件失败: {}", path.display()))?;
+
+        let config: CrawlConfig = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&content)
+                .with_context(|| format!("解析YAML配置失败: {}", path.display()))?,
+            _ => toml::from_str(&content)
+                .with_context(|| format!("解析TOML配置失败: {}", path.display()))?,
+        };
+
+        if config.jobs.is_empty() {
+            return Err(anyhow::anyhow!("配置文件中没有任何任务: {}", path.display()));
+        }
+        for job in &config.jobs {
+            job.validate().with_context(|| format!("任务 {} 的配置有误: {}", job.name, path.display()))?;
+        }
+
+        Ok(config)
+    }
+}
+
+impl JobConfig {
+    /// 检查任务类型不支持的设置：租房列表没有详情页爬虫，也不支持在售/成交的筛选段
+    pub fn validate(&self) -> Result<()> {
+        if self.kind != ListingKind::Zufang {
+            return Ok(());
+        }
+        if self.details {
+            return Err(anyhow::anyhow!("租房数据暂不支持爬取详情页，请去掉 details 设置"));
+        }
+        // 租房站的筛选段与在售/成交不同，只能原样传递未建模的筛选段
+        let unsupported: Vec<String> = self.search_filter()
+            .segments()
+            .into_iter()
+            .filter(|segment| !matches!(segment, FilterSegment::Other { .. }))
+            .map(|segment| segment.to_string())
+            .collect();
+        if !unsupported.is_empty() {
+            return Err(anyhow::anyhow!("租房列表不支持以下筛选段: {}", unsupported.join(", ")));
+        }
+        Ok(())
+    }
+
+    /// 合并结构化筛选条件与原始筛选段
+    pub fn search_filter(&self) -> SearchFilter {
+        SearchFilter::from_segments(&self.filters).merge(self.filter.clone())
+    }
+
+    pub fn search_url(&self) -> SearchUrl {
+        let mut search = SearchUrl::new(&self.city, self.kind).filters(self.search_filter().segments());
+        if !self.district.is_empty() {
+            search = search.area(&self.district);
+        }
+        if let Some(base_url) = &self.base_url {
+            search = search.base_url(base_url);
+        }
+        search
+    }
+
+    pub fn output_stem(&self) -> String {
+        format!("{}_data", self.name)
+    }
+
+    pub fn detail_output_stem(&self) -> String {
+        format!("{}_detail", self.name)
+    }
+
+    /// 在售任务的价格历史文件名前缀，历史跨多次运行累积，不会被覆盖
+    pub fn history_output_stem(&self) -> String {
+        format!("{}_history", self.name)
+    }
+}
+
+fn default_output_dir() -> PathBuf {
+    PathBuf::from(".")
+}
+
+fn default_city() -> String {
+    "cq".to_string()
+}
+
+fn default_start_page() -> u32 {
+    1
+}
+
+fn default_concurrency() -> usize {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(toml: &str) -> JobConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn zufang_job_rejects_details_and_listing_filters() {
+        let base = "name = \"zufang\"\nkind = \"zufang\"\ndistrict = \"dazhulin\"\n";
+        assert!(job(base).validate().is_ok());
+        // 未建模的筛选段原样传递
+        assert!(job(&format!("{}filters = [\"brp2000\"]\n", base)).validate().is_ok());
+
+        let error = job(&format!("{}details = true\n", base)).validate().unwrap_err();
+        assert!(error.to_string().contains("详情页"), "{}", error);
+        let error = job(&format!("{}filters = [\"de1\"]\nfilter = {{ rooms = [3] }}\n", base)).validate().unwrap_err();
+        assert!(error.to_string().contains("l3, de1"), "{}", error);
+
+        let ershoufang = "name = \"ershoufang\"\nkind = \"ershoufang\"\ndistrict = \"dazhulin\"\ndetails = true\nfilters = [\"de1\"]\n";
+        assert!(job(ershoufang).validate().is_ok());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::checkpoint::Checkpoint;
+use crate::error::ScrapeError;
+use crate::pagination::{split_search, PageInfo, MAX_PAGES};
+use crate::retry::{ErrorClass, RetryPolicy};
+use crate::search_url::SearchUrl;
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::future::Future;
+
+/// 查询拆分的最大层数，避免在极端情况下无限细分
+const MAX_SPLIT_DEPTH: u32 = 4;
+
+/// 单个列表页的解析结果
+#[derive(Debug)]
+pub struct ListPage<T> {
+    pub items: Vec<T>,
+    pub info: PageInfo,
+}
+
+impl<T> ListPage<T> {
+    /// 没有解析出任何房源、页面上也没有"共找到0套"时返回 [`ScrapeError::EmptyPage`]
+    pub fn non_empty(self) -> Result<Self, ScrapeError> {
+        if self.items.is_empty() && self.info.total_count != Some(0) {
+            return Err(ScrapeError::EmptyPage);
+        }
+        Ok(self)
+    }
+}
+
+/// 列表页爬虫的公共接口，翻页、拆分查询和去重由 [`crawl`] 统一处理
+pub trait ListScraper {
+    /// 记录需要能序列化，以便逐页写入断点的中间结果
+    type Item: Serialize + DeserializeOwned;
+
+    /// 爬取并解析单个列表页
+    fn scrape_page(&self, url: &str) -> impl Future<Output = Result<ListPage<Self::Item>, ScrapeError>>;
+
+    /// 记录的去重键，切片之间共享边界时同一条记录可能出现两次
+    fn item_key(item: &Self::Item) -> String;
+
+    /// 单页失败时的重试策略
+    fn retry_policy(&self) -> &RetryPolicy;
+
+    /// 同时在途的列表页请求数
+    fn concurrency(&self) -> usize;
+}
+
+/// 重试耗尽后仍然失败的页
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedPage {
+    pub page: u32,
+    pub url: String,
+    pub attempts: u32,
+    pub error: String,
+    /// 自动确定页数时查询的第一页失败：结果总数未知，该查询其余的页都没有爬取
+    #[serde(default)]
+    pub search_incomplete: bool,
+}
+
+impl FailedPage {
+    /// 用于报告的说明，如 "第 1 页 https://... (HTTP请求失败: 500)"
+    pub fn describe(&self) -> String {
+        let mut text = format!("第 {} 页 {} ({})", self.page, self.url, self.error);
+        if self.search_incomplete {
+            text.push_str("，无法确定页数，该查询其余的页未爬取");
+        }
+        text
+    }
+}
+
+/// 一次爬取的结果：成功解析的记录，以及永久失败的页
+#[derive(Debug)]
+pub struct CrawlReport<T> {
+    pub items: Vec<T>,
+    pub failed_pages: Vec<FailedPage>,
+}
+
+/// 爬取 `search` 对应的列表
+///
+/// `end_page` 为 None 时从第一页读取结果总数和分页信息，自动确定页数；
+/// 结果超过网站100页上限时按总价、面积拆分成更细的查询，保证每套房源都能被访问到。
+/// 指定 `end_page` 时只爬取该范围内实际存在的页。
+///
+/// 第一页之后的页按 [`ListScraper::concurrency`] 并发爬取，结果仍按页码顺序合并。
+/// 每一页要么重试到成功，要么记录在 [`CrawlReport::failed_pages`] 中；需要登录时整个爬取中止。
+///
+/// 每页的结果都会立即写入 `checkpoint`，断点中已完成的页不会再次请求。
+pub async fn crawl<S: ListScraper>(
+    scraper: &S,
+    search: &SearchUrl,
+    start_page: u32,
+    end_page: Option<u32>,
+    checkpoint: &mut Checkpoint,
+) -> Result<CrawlReport<S::Item>> {
+    let mut seen = HashSet::new();
+    let mut report = CrawlReport { items: Vec::new(), failed_pages: Vec::new() };
+
+    if checkpoint.completed_pages() > 0 {
+        collect::<S>(checkpoint.load_items()?, &mut seen, &mut report.items);
+        println!(
+            "从断点恢复: 已完成 {} 页，已有 {} 条数据",
+            checkpoint.completed_pages(),
+            report.items.len()
+        );
+    }
+
+    crawl_search(scraper, search, start_page, end_page, 0, &mut seen, &mut report, checkpoint).await?;
+
+    if !report.failed_pages.is_empty() {
+        println!("\n有 {} 页在重试后仍然失败:", report.failed_pages.len());
+        for failed in &report.failed_pages {
+            println!("  {} - {} 次尝试", failed.describe(), failed.attempts);
+        }
+    }
+
+    Ok(report)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn crawl_search<S: ListScraper>(
+    scraper: &S,
+    search: &SearchUrl,
+    start_page: u32,
+    end_page: Option<u32>,
+    depth: u32,
+    seen: &mut HashSet<String>,
+    report: &mut CrawlReport<S::Item>,
+    checkpoint: &mut Checkpoint,
+) -> Result<()> {
+    let first_url = search.page_url(start_page);
+
+    let first = match checkpoint.completed(&first_url) {
+        // 断点中已完成的页只需要分页信息，记录已经从中间结果中读取
+        Some(info) => {
+            println!("第 {} 页已完成，跳过: {}", start_page, first_url);
+            ListPage { items: Vec::new(), info }
+        }
+        None => {
+            println!("正在爬取第 {} 页: {}", start_page, first_url);
+            match scrape_with_retry(scraper, start_page, &first_url).await? {
+                Ok(page) => {
+                    println!("第 {} 页成功获取 {} 条数据", start_page, page.items.len());
+                    checkpoint.record_page(&first_url, page.info, &page.items)?;
+                    page
+                }
+                Err(mut failed) => {
+                    // 自动模式下页数来自第一页，失败时不能当作只有一页，整个查询记为未完成
+                    failed.search_incomplete = end_page.is_none();
+                    checkpoint.record_failure(&failed)?;
+                    report.failed_pages.push(failed);
+                    if end_page.is_none() {
+                        println!("第 {} 页失败，无法确定页数，该查询其余的页未爬取，可以使用 --resume 重新爬取", start_page);
+                        return Ok(());
+                    }
+                    ListPage { items: Vec::new(), info: PageInfo::default() }
+                }
+            }
+        }
+    };
+
+    let info = first.info;
+    if let Some(count) = info.total_count {
+        println!("共找到 {} 套房源，分页显示 {} 页", count, info.total_pages.unwrap_or(0));
+    }
+
+    // 自动模式下结果超过100页上限时拆分查询
+    if end_page.is_none() && info.is_truncated() && depth < MAX_SPLIT_DEPTH {
+        if let Some((dimension, slices)) = split_search(search) {
+            println!(
+                "结果数超过 {} 页上限，按{}拆分为 {} 个查询",
+                MAX_PAGES, dimension, slices.len()
+            );
+            for slice in &slices {
+                println!("\n--- 切片查询: {} ---", slice);
+                Box::pin(crawl_search(scraper, slice, 1, None, depth + 1, seen, report, checkpoint)).await?;
+            }
+            return Ok(());
+        }
+        println!("无法继续拆分查询，只能爬取前 {} 页", MAX_PAGES);
+    }
+
+    collect::<S>(first.items, seen, &mut report.items);
+
+    let last_page = match (end_page, info.page_count()) {
+        (Some(end), Some(pages)) => end.min(pages),
+        (Some(end), None) => end,
+        (None, Some(pages)) => pages,
+        // 没有分页信息时说明只有一页结果
+        (None, None) => start_page,
+    };
+
+    let pending: Vec<u32> = ((start_page + 1)..=last_page)
+        .filter(|&page| checkpoint.completed(&search.page_url(page)).is_none())
+        .collect();
+    let skipped = (last_page.saturating_sub(start_page) as usize).saturating_sub(pending.len());
+    if skipped > 0 {
+        println!("断点中已完成 {} 页，跳过", skipped);
+    }
+
+    // 其余页并发爬取，buffered 按页码顺序产出结果，保证输出顺序与顺序爬取时一致
+    let mut pages = stream::iter(pending)
+        .map(|page| async move {
+            let url = search.page_url(page);
+            println!("正在爬取第 {} 页: {}", page, url);
+            (page, url.clone(), scrape_with_retry(scraper, page, &url).await)
+        })
+        .buffered(scraper.concurrency().max(1));
+
+    while let Some((page, url, outcome)) = pages.next().await {
+        match outcome? {
+            Ok(results) => {
+                println!("第 {} 页成功获取 {} 条数据", page, results.items.len());
+                checkpoint.record_page(&url, results.info, &results.items)?;
+                collect::<S>(results.items, seen, &mut report.items);
+            }
+            Err(failed) => {
+                checkpoint.record_failure(&failed)?;
+                report.failed_pages.push(failed);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 按重试策略爬取单页
+///
+/// 外层 Err 表示需要中止整个爬取（如需要登录），内层 Err 表示这一页在重试后仍然失败。
+async fn scrape_with_retry<S: ListScraper>(scraper: &S, page: u32, url: &str) -> Result<Result<ListPage<S::Item>, FailedPage>> {
+    let label = format!("第 {} 页", page);
+    Ok(retry(scraper.retry_policy(), &label, || scraper.scrape_page(url))
+        .await?
+        .map_err(|(attempts, error)| FailedPage {
+            page,
+            url: url.to_string(),
+            attempts,
+            error: error.to_string(),
+            search_incomplete: false,
+        }))
+}
+
+/// 按重试策略反复执行 `scrape`，`label` 用于日志，如 "第 3 页"
+///
+/// 外层 Err 表示需要中止整个爬取（如需要登录），内层 Err 给出尝试次数和最后一次的错误。
+pub(crate) async fn retry<T, F, Fut>(policy: &RetryPolicy, label: &str, mut scrape: F) -> Result<Result<T, (u32, ScrapeError)>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ScrapeError>>,
+{
+    let mut attempt = 1;
+
+    loop {
+        let error = match scrape().await {
+            Ok(result) => return Ok(Ok(result)),
+            Err(e) => e,
+        };
+
+        println!("{}第 {} 次尝试失败: {}", label, attempt, error);
+
+        if error.class() == ErrorClass::Fatal {
+            return Err(error.into());
+        }
+
+        match policy.backoff(&error, attempt) {
+            Some(wait) => {
+                if error.class() == ErrorClass::Throttled {
+                    println!("遇到人机验证或限流，等待 {:.1} 秒后重试...", wait.as_secs_f64());
+                } else {
+                    println!("等待 {:.1} 秒后重试...", wait.as_secs_f64());
+                }
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+            }
+            None => {
+                println!("{}放弃重试，记录为失败", label);
+                return Ok(Err((attempt, error)));
+            }
+        }
+    }
+}
+
+/// 合并一页结果，跳过切片边界上重复出现的记录
+fn collect<S: ListScraper>(items: Vec<S::Item>, seen: &mut HashSet<String>, all_results: &mut Vec<S::Item>) {
+    for item in items {
+        if seen.insert(S::item_key(&item)) {
+            all_results.push(item);
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::crawl::retry;
+use crate::error::ScrapeError;
+use crate::retry::RetryPolicy;
+use crate::search_url::rebase_url;
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use scraper::{ElementRef, Html, Selector};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+
+/// 详情页爬虫共用的设置
+#[derive(Debug, Clone)]
+pub struct DetailOptions {
+    /// 单个详情页失败时的重试策略
+    pub retry_policy: RetryPolicy,
+    /// 同时在途的详情页请求数，实际请求速率仍受 Fetcher 的限速约束
+    pub concurrency: usize,
+    /// 向该地址请求详情页，而不是列表链接中的 `https://{city}.ke.com`
+    pub base_url: Option<String>,
+}
+
+impl Default for DetailOptions {
+    fn default() -> Self {
+        Self {
+            retry_policy: RetryPolicy::default(),
+            concurrency: 1,
+            base_url: None,
+        }
+    }
+}
+
+impl DetailOptions {
+    /// 列表记录中的链接，设置了 `base_url` 时换到该站点，链接为空时返回 None
+    pub fn rebase(&self, url: &str) -> Option<String> {
+        if url.is_empty() {
+            return None;
+        }
+        Some(match &self.base_url {
+            Some(base_url) => rebase_url(url, base_url),
+            None => url.to_string(),
+        })
+    }
+}
+
+/// 详情页爬虫的公共接口：沿着列表记录中的链接逐条抓取详情页，由 [`crawl_details`] 统一处理重试和并发
+pub trait DetailScraper: Sized {
+    /// 列表页上的一条记录
+    type Summary;
+    /// 详情页的解析结果
+    type Detail;
+
+    /// 重试策略、并发数和请求地址，`with_*` 方法通过 [`DetailScraper::options_mut`] 修改
+    fn options(&self) -> &DetailOptions;
+
+    fn options_mut(&mut self) -> &mut DetailOptions;
+
+    /// 列表记录中的详情链接，没有链接时为空
+    fn summary_url(summary: &Self::Summary) -> &str;
+
+    /// 记录对应的详情页地址，默认就是列表记录中的链接，没有链接时返回 None
+    fn detail_url(&self, summary: &Self::Summary) -> Option<String> {
+        self.options().rebase(Self::summary_url(summary))
+    }
+
+    /// 爬取并解析单个详情页
+    fn scrape_detail(&self, url: &str) -> impl Future<Output = Result<Self::Detail, ScrapeError>>;
+
+    /// 检查详情页是否属于这条列表记录，不属于时返回错误，该记录按详情页失败处理；默认不检查
+    fn check_detail(&self, _summary: &Self::Summary, _detail: &Self::Detail) -> Result<(), ScrapeError> {
+        Ok(())
+    }
+
+    /// 依次爬取每条记录的详情页，结果与列表记录一一对应
+    fn scrape_details(
+        &self,
+        summaries: Vec<Self::Summary>,
+    ) -> impl Future<Output = Result<DetailReport<Self::Summary, Self::Detail>>> {
+        crawl_details(self, summaries)
+    }
+
+    /// 设置单个详情页失败时的重试策略
+    fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.options_mut().retry_policy = retry_policy;
+        self
+    }
+
+    /// 设置同时在途的详情页请求数，实际请求速率仍受 Fetcher 的限速约束
+    fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.options_mut().concurrency = concurrency.max(1);
+        self
+    }
+
+    /// 向 `base_url` 请求详情页，而不是列表链接中的 `https://{city}.ke.com`
+    fn with_base_url(mut self, base_url: Option<String>) -> Self {
+        self.options_mut().base_url = base_url;
+        self
+    }
+}
+
+/// 列表记录与其详情页合并后的记录，序列化时列表字段展开在顶层
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetailRecord<S, D> {
+    #[serde(flatten)]
+    pub summary: S,
+    /// 详情页解析结果，没有链接或抓取失败时为 None
+    pub detail: Option<D>,
+}
+
+/// 重试耗尽后仍然失败的详情页
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedDetail {
+    pub url: String,
+    pub attempts: u32,
+    pub error: String,
+}
+
+/// 一次详情页爬取的结果：每条列表记录都有一条合并记录，失败的详情页另外列出
+#[derive(Debug)]
+pub struct DetailReport<S, D> {
+    pub records: Vec<DetailRecord<S, D>>,
+    pub failed: Vec<FailedDetail>,
+}
+
+/// 依次抓取 `summaries` 中每条记录的详情页
+///
+/// 按 [`DetailOptions::concurrency`] 并发请求，结果仍按列表顺序排列。
+/// 单个详情页失败或不属于对应的列表记录时不影响其他记录，需要登录时整个爬取中止。
+pub async fn crawl_details<S: DetailScraper>(
+    scraper: &S,
+    summaries: Vec<S::Summary>,
+) -> Result<DetailReport<S::Summary, S::Detail>> {
+    let total = summaries.len();
+    let mut report = DetailReport { records: Vec::with_capacity(total), failed: Vec::new() };
+
+    let mut details = stream::iter(summaries.into_iter().enumerate())
+        .map(|(index, summary)| async move {
+            let outcome = match scraper.detail_url(&summary) {
+                Some(url) => {
+                    println!("正在爬取详情页 {}/{}: {}", index + 1, total, url);
+                    let label = format!("详情页 {} ", url);
+                    let result = retry(&scraper.options().retry_policy, &label, || async {
+                        let detail = scraper.scrape_detail(&url).await?;
+                        scraper.check_detail(&summary, &detail)?;
+                        Ok(detail)
+                    })
+                    .await;
+                    Some((url, result))
+                }
+                None => None,
+            };
+            (summary, outcome)
+        })
+        .buffered(scraper.options().concurrency.max(1));
+
+    while let Some((summary, outcome)) = details.next().await {
+        let detail = match outcome {
+            Some((url, result)) => match result? {
+                Ok(detail) => Some(detail),
+                Err((attempts, error)) => {
+                    report.failed.push(FailedDetail { url, attempts, error: error.to_string() });
+                    None
+                }
+            },
+            None => None,
+        };
+        report.records.push(DetailRecord { summary, detail });
+    }
+
+    println!(
+        "详情页爬取完成: {} 条记录，{} 个详情页失败",
+        report.records.len(),
+        report.failed.len()
+    );
+    Ok(report)
+}
+
+/// 列表记录和详情页上的编号都存在且不相同时返回错误，说明详情页属于另一条记录（如链接被重定向了）
+pub(crate) fn check_id<T: PartialEq + fmt::Display>(
+    field: &'static str,
+    label: &str,
+    expected: Option<&T>,
+    found: Option<&T>,
+) -> Result<(), ScrapeError> {
+    match (expected, found) {
+        (Some(expected), Some(found)) if expected != found => Err(ScrapeError::Parse {
+            field,
+            reason: format!("详情页的{} {} 与列表记录 {} 不一致", label, found, expected),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// 页面的规范地址：`<link rel="canonical">`，没有时取 `<meta property="og:url">`
+pub(crate) fn canonical_url(document: &Html) -> Option<String> {
+    let link_selector = Selector::parse("link[rel='canonical']").unwrap();
+    let meta_selector = Selector::parse("meta[property='og:url']").unwrap();
+    document.select(&link_selector)
+        .find_map(|el| el.value().attr("href"))
+        .or_else(|| document.select(&meta_selector).find_map(|el| el.value().attr("content")))
+        .map(str::to_string)
+}
+
+/// 详情页上的属性列表，如 "基本属性"、"交易属性"：`<li><span class="label">梯户比例</span>两梯四户</li>`
+///
+/// 值可能直接跟在 label 后面，也可能包在 span 里，这里取整个 li 的文本去掉 label，并合并多余的空白。
+pub(crate) fn attributes(document: &Html, selector: &str) -> BTreeMap<String, String> {
+    let item_selector = Selector::parse(selector).unwrap();
+    let label_selector = Selector::parse(".label").unwrap();
+    document.select(&item_selector)
+        .filter_map(|li| {
+            let name = li.select(&label_selector).next()?.text().collect::<String>().trim().to_string();
+            let text = li.text().collect::<String>();
+            let value = text.trim().strip_prefix(name.as_str()).unwrap_or(&text);
+            Some((name, value.split_whitespace().collect::<Vec<_>>().join(" ")))
+        })
+        .filter(|(name, _)| !name.is_empty())
+        .collect()
+}
+
+/// 元素自身的文本，不含子元素（如 label）中的文本
+pub(crate) fn text_without_children(element: &ElementRef) -> String {
+    element.children()
+        .filter_map(|node| node.value().as_text().map(|text| text.to_string()))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// 页面上 "暂无数据"、"未知" 等占位文本视为缺失
+pub(crate) fn known(value: Option<&String>) -> Option<String> {
+    value
+        .filter(|value| !value.is_empty() && !matches!(value.as_str(), "暂无数据" | "未知"))
+        .cloned()
+}
+
+/// "有" / "无"，其他文本视为未知
+pub(crate) fn yes_no(text: &str) -> Option<bool> {
+    match text {
+        "有" => Some(true),
+        "无" => Some(false),
+        _ => None,
+    }
+}
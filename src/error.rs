@@ -0,0 +1,55 @@
+use reqwest::StatusCode;
+use std::fmt;
+
+/// 抓取和解析页面时的错误
+///
+/// 重试策略和报告都按变体区分，不再依赖错误信息里的中文文本。
+#[derive(Debug)]
+pub enum ScrapeError {
+    /// 请求没有发出去或连接中断
+    Network(reqwest::Error),
+    /// 请求地址无法解析，通常来自配置文件或 --url，重试也不会成功
+    InvalidUrl { url: String, reason: String },
+    /// 服务器返回非2xx状态码
+    Http(StatusCode),
+    /// 解压或UTF-8解码失败
+    Decode(String),
+    /// 返回的是人机验证页面，或被重定向到验证页
+    Captcha,
+    /// 被重定向到登录页，通常是Cookie过期
+    LoginRequired { location: String },
+    /// 页面正常返回但没有解析出任何房源，且页面上也没有"共找到0套"的提示
+    EmptyPage,
+    /// 某个字段无法解析
+    Parse { field: &'static str, reason: String },
+}
+
+impl fmt::Display for ScrapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScrapeError::Network(e) => write!(f, "网络请求失败: {}", e),
+            ScrapeError::InvalidUrl { url, reason } => write!(f, "无效的URL {}: {}", url, reason),
+            ScrapeError::Http(status) => write!(f, "HTTP请求失败: {}", status),
+            ScrapeError::Decode(e) => write!(f, "响应解码失败: {}", e),
+            ScrapeError::Captcha => write!(f, "遇到人机验证，请稍后重试"),
+            ScrapeError::LoginRequired { location } => write!(f, "需要登录，请更新Cookie (重定向到 {})", location),
+            ScrapeError::EmptyPage => write!(f, "页面没有解析出任何房源，可能是页面结构发生变化或遇到反爬虫机制"),
+            ScrapeError::Parse { field, reason } => write!(f, "字段 {} 解析失败: {}", field, reason),
+        }
+    }
+}
+
+impl std::error::Error for ScrapeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ScrapeError::Network(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ScrapeError {
+    fn from(e: reqwest::Error) -> Self {
+        ScrapeError::Network(e)
+    }
+}
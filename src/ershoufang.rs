@@ -1,104 +1,130 @@
 use anyhow::Result;
-use reqwest::Client;
 use scraper::{Html, Selector};
+use crate::checkpoint::Checkpoint;
+use crate::crawl::{crawl, CrawlReport, ListPage, ListScraper};
+use crate::error::ScrapeError;
+use crate::fetch::Fetcher;
+use crate::pagination::PageInfo;
+use crate::retry::RetryPolicy;
+use crate::search_url::SearchUrl;
+use crate::house::Floor;
+use crate::listing::ListingId;
+use crate::region::{city_of, Area, Region, RegionFilter};
+use crate::text::{parse_age_days, parse_integer, parse_number};
+use crate::xiaoqu::community_id;
+use chrono::{Duration, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErshoufangData {
+    /// 房源编号（housecode），取自详情链接或列表项的 data 属性
+    pub id: Option<ListingId>,
     pub title: String,
+    /// 总价（万元）
+    pub total_price: Option<f64>,
+    /// 单价（元/平米）
+    pub unit_price: Option<f64>,
+    /// 建筑面积（平方米）
+    pub area: Option<f64>,
+    pub layout: String,
+    pub floor: Floor,
+    pub build_year: Option<u32>,
+    pub community: String,
+    /// 小区编号，取自小区链接 /xiaoqu/{id}/，用于关联小区表
+    pub community_id: Option<String>,
+    /// 所在城市、区县和商圈，取自列表项的商圈链接和列表页的区域筛选栏
+    pub region: Region,
+    pub tags: Vec<String>,
+    pub url: String,
+    /// 关注人数
+    pub follower_count: Option<u32>,
+    /// 由 "N天以前发布" 和爬取日期推算的挂牌日期，按月、年发布的只是近似值
+    pub listed_on: Option<NaiveDate>,
+    /// 页面上的原始文本，用于核对解析结果
+    pub raw: ErshoufangRaw,
+}
+
+/// 在售房源各字段在页面上的原始文本，没有找到时为空
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErshoufangRaw {
     pub total_price: String,
     pub unit_price: String,
     pub area: String,
-    pub layout: String,
     pub floor: String,
     pub build_year: String,
-    pub community: String,
-    pub district: String,
-    pub tags: Vec<String>,
-    pub url: String,
-    pub attention_count: String,
+    pub follow_info: String,
     pub publish_time: String,
 }
 
 pub struct ErshoufangScraper {
-    client: Client,
-    cookies: String,
+    fetcher: Fetcher,
+    retry_policy: RetryPolicy,
+    concurrency: usize,
 }
 
 impl ErshoufangScraper {
-    pub fn new(cookies: String) -> Self {
-        let client = Client::builder()
-            .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-            .build()
-            .unwrap();
+    pub fn new(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher,
+            retry_policy: RetryPolicy::default(),
+            concurrency: 1,
+        }
+    }
 
-        Self { client, cookies }
+    /// 设置单页失败时的重试策略
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
     }
 
-    pub async fn scrape_ershoufang_page(&self, url: &str) -> Result<Vec<ErshoufangData>> {
-        let response = self.client
-            .get(url)
-            .header("Cookie", &self.cookies)
-            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
-            .header("Accept-Language", "zh-CN,zh;q=0.9,en;q=0.8")
-            .header("Accept-Encoding", "gzip, deflate, br")
-            .header("Connection", "keep-alive")
-            .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-            .header("Host", "cq.ke.com")
-            .header("Referer", "https://cq.ke.com/")
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("HTTP请求失败: {}", response.status()));
-        }
+    /// 设置同时在途的列表页请求数，实际请求速率仍受 Fetcher 的限速约束
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
 
-        let bytes = response.bytes().await?;
-
-        let html_content = if bytes.len() > 2 && bytes[0] == 0x1f && bytes[1] == 0x8b {
-            println!("检测到gzip压缩数据，正在解压...");
-            use std::io::Read;
-            use flate2::read::GzDecoder;
-
-            let mut decoder = GzDecoder::new(&bytes[..]);
-            let mut decompressed = String::new();
-            decoder.read_to_string(&mut decompressed)
-                .map_err(|e| anyhow::anyhow!("解压失败: {}", e))?;
-            decompressed
-        } else {
-            String::from_utf8(bytes.to_vec())
-                .map_err(|e| anyhow::anyhow!("UTF-8解码失败: {}", e))?
-        };
+    pub async fn scrape_ershoufang_page(&self, url: &str) -> Result<ListPage<ErshoufangData>, ScrapeError> {
+        let html_content = self.fetcher.fetch(url).await?;
+        self.parse_page(&html_content).non_empty()
+    }
 
-        let document = Html::parse_document(&html_content);
+    /// 解析列表页 HTML，不发起任何网络请求，挂牌日期按今天推算
+    pub fn parse_page(&self, html_content: &str) -> ListPage<ErshoufangData> {
+        self.parse_page_on(html_content, Local::now().date_naive())
+    }
 
-        if html_content.contains("人机验证") || html_content.contains("CAPTCHA") {
-            return Err(anyhow::anyhow!("遇到人机验证，请稍后重试"));
-        }
+    /// 解析 `crawled_on` 当天抓取的列表页 HTML，重新解析归档时传入归档的抓取日期
+    pub fn parse_page_on(&self, html_content: &str, crawled_on: NaiveDate) -> ListPage<ErshoufangData> {
+        let document = Html::parse_document(html_content);
 
         // 使用正确的选择器：sellListContent下的li元素
         let list_selector = Selector::parse(".sellListContent li.clear").unwrap();
         let items = document.select(&list_selector);
+        let regions = RegionFilter::parse(&document, "ershoufang");
         
         let mut results: Vec<ErshoufangData> = Vec::new();
         
         for item in items {
-            if let Ok(ershoufang_data) = self.parse_list_item(&item) {
-                results.push(ershoufang_data);
+            match self.parse_list_item(&item, crawled_on, &regions) {
+                Ok(ershoufang_data) => results.push(ershoufang_data),
+                Err(e) => println!("跳过列表项: {}", e),
             }
         }
 
         println!("成功解析 {} 条二手房数据", results.len());
-        Ok(results)
+        ListPage { items: results, info: PageInfo::parse(&document) }
     }
 
-    fn parse_list_item(&self, item: &scraper::ElementRef) -> Result<ErshoufangData> {
-        // 解析标题和链接
+    fn parse_list_item(&self, item: &scraper::ElementRef, crawled_on: NaiveDate, regions: &RegionFilter) -> Result<ErshoufangData, ScrapeError> {
+        // 解析标题和链接，没有标题的li不是房源（如广告位）
         let title_selector = Selector::parse(".title a").unwrap();
         let title = item.select(&title_selector)
             .next()
             .map(|el| el.text().collect::<String>().trim().to_string())
-            .unwrap_or_else(|| "未知".to_string());
+            .ok_or_else(|| ScrapeError::Parse {
+                field: "title",
+                reason: "列表项中没有 .title a".to_string(),
+            })?;
         
         let url = item.select(&title_selector)
             .next()
@@ -110,46 +136,51 @@ impl ErshoufangScraper {
         let total_price_selector = Selector::parse(".totalPrice").unwrap();
         let total_price = item.select(&total_price_selector)
             .next()
-            .map(|el| {
-                let text = el.text().collect::<String>();
-                // 提取数字部分，去掉"万"等文字
-                text.chars()
-                    .filter(|c| c.is_digit(10) || *c == '.')
-                    .collect::<String>()
-            })
-            .unwrap_or_else(|| "未知".to_string());
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
 
         // 解析单价
         let unit_price_selector = Selector::parse(".unitPrice span").unwrap();
         let unit_price = item.select(&unit_price_selector)
             .next()
             .map(|el| el.text().collect::<String>().trim().to_string())
-            .unwrap_or_else(|| "未知".to_string());
+            .unwrap_or_default();
 
         // 解析房屋信息（户型、面积、朝向、楼层、建筑年份）
         let house_info_selector = Selector::parse(".houseInfo").unwrap();
         let house_info = item.select(&house_info_selector)
             .next()
             .map(|el| el.text().collect::<String>())
-            .unwrap_or_else(|| "".to_string());
+            .unwrap_or_default();
         
         let (layout, area, floor, build_year) = self.parse_house_info(&house_info);
 
         // 解析小区信息
         let community_selector = Selector::parse(".positionInfo a").unwrap();
-        let community = item.select(&community_selector)
-            .next()
+        let community_link = item.select(&community_selector).next();
+        let community = community_link
             .map(|el| el.text().collect::<String>().trim().to_string())
             .unwrap_or_else(|| "未知".to_string());
+        let community_id = community_link
+            .and_then(|el| el.value().attr("href"))
+            .and_then(community_id);
+
+        // 解析商圈：小区链接后面的 /ershoufang/{商圈}/ 链接，区县从筛选栏的层级中查找
+        let bizcircle = item.select(&community_selector).find_map(|el| Area::from_link(&el));
+        let city = item.select(&title_selector)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .and_then(city_of);
+        let region = regions.locate(bizcircle, city);
 
         // 解析关注信息
         let follow_info_selector = Selector::parse(".followInfo").unwrap();
         let follow_info = item.select(&follow_info_selector)
             .next()
             .map(|el| el.text().collect::<String>())
-            .unwrap_or_else(|| "".to_string());
+            .unwrap_or_default();
         
-        let (attention_count, publish_time) = self.parse_follow_info(&follow_info);
+        let (follow_info, publish_time) = self.parse_follow_info(&follow_info);
 
         // 解析标签
         let tag_selector = Selector::parse(".tag span").unwrap();
@@ -158,31 +189,46 @@ impl ErshoufangScraper {
             .filter(|tag| !tag.is_empty())
             .collect();
 
-        Ok(ErshoufangData {
-            title,
-            total_price: if total_price.is_empty() { "未知".to_string() } else { format!("{}万", total_price) },
+        let raw = ErshoufangRaw {
+            total_price,
             unit_price,
             area,
-            layout,
             floor,
             build_year,
+            follow_info,
+            publish_time,
+        };
+
+        Ok(ErshoufangData {
+            id: ListingId::from_element(item),
+            title,
+            total_price: parse_number(&raw.total_price),
+            unit_price: parse_number(&raw.unit_price),
+            area: parse_number(&raw.area),
+            layout,
+            floor: Floor::parse(&raw.floor),
+            build_year: parse_integer(&raw.build_year),
             community,
-            district: "重庆渝北".to_string(), // 可以根据实际情况动态获取
+            community_id,
+            region,
             tags,
             url,
-            attention_count,
-            publish_time,
+            follower_count: parse_integer(&raw.follow_info),
+            listed_on: parse_age_days(&raw.publish_time)
+                .map(|days| crawled_on - Duration::days(days as i64)),
+            raw,
         })
     }
 
+    /// 拆分 "中楼层(共18层) | 2015年建 | 3室2厅 | 89.5平米 | 南 北"，返回 (户型, 面积, 楼层, 建成年份) 的原始文本
     fn parse_house_info(&self, house_info: &str) -> (String, String, String, String) {
-        let info = house_info.replace('\n', " ").replace('\r', " ");
+        let info = house_info.replace(['\n', '\r'], " ");
         let parts: Vec<&str> = info.split('|').collect();
         
         let mut layout = "未知".to_string();
-        let mut area = "未知".to_string();
-        let mut floor = "未知".to_string();
-        let mut build_year = "未知".to_string();
+        let mut area = String::new();
+        let mut floor = String::new();
+        let mut build_year = String::new();
 
         for part in parts {
             let part = part.trim();
@@ -192,7 +238,7 @@ impl ErshoufangScraper {
                 area = part.to_string();
             } else if part.contains("层") {
                 floor = part.to_string();
-            } else if part.contains("年") && part.chars().any(|c| c.is_digit(10)) {
+            } else if part.contains("年") && part.chars().any(|c| c.is_ascii_digit()) {
                 build_year = part.to_string();
             }
         }
@@ -200,54 +246,43 @@ impl ErshoufangScraper {
         (layout, area, floor, build_year)
     }
 
+    /// 拆分 "12人关注 / 3天以前发布"，返回 (关注信息, 发布时间) 的原始文本
     fn parse_follow_info(&self, follow_info: &str) -> (String, String) {
-        let info = follow_info.replace('\n', " ").replace('\r', " ");
-        let parts: Vec<&str> = info.split('/').collect();
-        
-        let attention_count = if parts.len() > 0 {
-            parts[0].trim().to_string()
-        } else {
-            "未知".to_string()
-        };
-        
-        let publish_time = if parts.len() > 1 {
-            parts[1].trim().to_string()
-        } else {
-            "未知".to_string()
-        };
+        let info = follow_info.replace(['\n', '\r'], " ");
+        let mut parts = info.split('/').map(|part| part.trim().to_string());
+        let follow = parts.next().unwrap_or_default();
+        let publish_time = parts.next().unwrap_or_default();
+        (follow, publish_time)
+    }
 
-        (attention_count, publish_time)
+    /// 爬取 `start_page` 到 `end_page` 的列表页，`end_page` 为 None 时根据第一页的分页信息自动确定页数
+    ///
+    /// 每页结果写入 `checkpoint`，已在断点中完成的页会被跳过。
+    pub async fn scrape_multiple_pages(&self, search: &SearchUrl, start_page: u32, end_page: Option<u32>, checkpoint: &mut Checkpoint) -> Result<CrawlReport<ErshoufangData>> {
+        crawl(self, search, start_page, end_page, checkpoint).await
     }
+}
 
-    pub async fn scrape_multiple_pages(&self, base_url: &str, start_page: u32, end_page: u32) -> Result<Vec<ErshoufangData>> {
-        let mut all_results: Vec<ErshoufangData> = Vec::new();
-
-        for page in start_page..=end_page {
-            let url = base_url.replace("pg1", &format!("pg{}", page));
-
-            println!("正在爬取第 {} 页: {}", page, url);
-
-            match self.scrape_ershoufang_page(&url).await {
-                Ok(page_results) => {
-                    println!("第 {} 页成功获取 {} 条数据", page, page_results.len());
-                    all_results.extend(page_results);
-                },
-                Err(e) => {
-                    println!("第 {} 页爬取失败: {}", page, e);
-                    if e.to_string().contains("人机验证") {
-                        println!("遇到人机验证，等待5秒后重试...");
-                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                        continue;
-                    }
-                }
-            }
+impl ListScraper for ErshoufangScraper {
+    type Item = ErshoufangData;
 
-            if page < end_page {
-                println!("等待1秒后继续...");
-                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-            }
+    async fn scrape_page(&self, url: &str) -> Result<ListPage<ErshoufangData>, ScrapeError> {
+        self.scrape_ershoufang_page(url).await
+    }
+
+    fn item_key(item: &ErshoufangData) -> String {
+        match &item.id {
+            Some(id) => id.to_string(),
+            None if !item.url.is_empty() => item.url.clone(),
+            None => item.title.clone(),
         }
+    }
+
+    fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
 
-        Ok(all_results)
+    fn concurrency(&self) -> usize {
+        self.concurrency
     }
 }
@@ -0,0 +1,235 @@
+use crate::detail::{attributes, check_id, known, yes_no, DetailOptions, DetailRecord, DetailScraper};
+use crate::ershoufang::ErshoufangData;
+use crate::error::ScrapeError;
+use crate::fetch::Fetcher;
+use crate::listing::ListingId;
+use crate::text::{parse_date, parse_integer};
+use crate::xiaoqu::community_id;
+use anyhow::Result;
+use chrono::NaiveDate;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 在售房源详情页上的信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErshoufangDetail {
+    /// 房源编号，与列表记录的 `id` 一致
+    pub listing_id: Option<ListingId>,
+    /// 小区编号，即 /xiaoqu/{id}/ 中的数字
+    pub community_id: Option<String>,
+    /// 小区坐标
+    pub coordinates: Option<Coordinates>,
+    /// 负责该房源的经纪人
+    pub agent: Option<Agent>,
+    /// 房屋朝向，如 "南 北"
+    pub orientation: Option<String>,
+    /// 户型结构，如 "平层"、"跃层"
+    pub structure: Option<String>,
+    /// 建筑结构，如 "钢混结构"
+    pub building_structure: Option<String>,
+    /// 梯户比例，如 "两梯四户"
+    pub elevator_ratio: Option<String>,
+    /// 是否配备电梯
+    pub has_elevator: Option<bool>,
+    /// 产权年限（年）
+    pub property_right_years: Option<u32>,
+    /// 交易权属，如 "商品房"
+    pub ownership: Option<String>,
+    /// 挂牌时间
+    pub listed_on: Option<NaiveDate>,
+    /// 上次交易时间
+    pub last_transaction: Option<NaiveDate>,
+    /// 房屋年限，如 "满五年"、"满两年"
+    pub holding_period: Option<String>,
+    /// 是否有抵押，详细信息见 `transaction_attributes["抵押信息"]`
+    pub mortgaged: Option<bool>,
+    /// "基本属性" 中的全部条目，页面上显示 "暂无数据" 的条目也保留
+    pub base_attributes: BTreeMap<String, String>,
+    /// "交易属性" 中的全部条目
+    pub transaction_attributes: BTreeMap<String, String>,
+}
+
+/// 经纬度（GCJ-02，与贝壳网地图一致）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Coordinates {
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Agent {
+    pub name: String,
+    pub phone: Option<String>,
+}
+
+/// 列表记录与在售详情合并后的记录
+pub type ErshoufangRecord = DetailRecord<ErshoufangData, ErshoufangDetail>;
+
+pub struct ErshoufangDetailScraper {
+    fetcher: Fetcher,
+    options: DetailOptions,
+}
+
+impl ErshoufangDetailScraper {
+    pub fn new(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher,
+            options: DetailOptions::default(),
+        }
+    }
+
+    pub async fn scrape_ershoufang_detail(&self, url: &str) -> Result<ErshoufangDetail, ScrapeError> {
+        let html_content = self.fetcher.fetch(url).await?;
+        let mut detail = self.parse_detail(&html_content);
+        // 房源已下架时会跳转到列表页或返回空页面
+        if detail.base_attributes.is_empty() && detail.transaction_attributes.is_empty() {
+            return Err(ScrapeError::EmptyPage);
+        }
+        if detail.listing_id.is_none() {
+            detail.listing_id = ListingId::from_url(url);
+        }
+        Ok(detail)
+    }
+
+    /// 解析详情页 HTML，不发起任何网络请求
+    pub fn parse_detail(&self, html_content: &str) -> ErshoufangDetail {
+        let document = Html::parse_document(html_content);
+
+        // 房源编号、小区编号和坐标也出现在页面脚本的配置里
+        let script_selector = Selector::parse("script").unwrap();
+        let scripts: String = document.select(&script_selector).flat_map(|el| el.text()).collect();
+
+        let base_attributes = attributes(&document, ".introContent .base li");
+        let transaction_attributes = attributes(&document, ".introContent .transaction li");
+        let base = |name: &str| known(base_attributes.get(name));
+        let transaction = |name: &str| known(transaction_attributes.get(name));
+
+        let listing_id = select_text(&document, ".houseRecord .info")
+            .and_then(|text| {
+                let digits: String = text.chars().take_while(|c| c.is_ascii_digit()).collect();
+                ListingId::parse(&digits)
+            })
+            .or_else(|| script_value(&scripts, "houseCode").and_then(|code| ListingId::parse(&code)));
+
+        // 小区编号优先取小区链接，没有链接时取页面脚本中的 resblockId
+        let community_id = select_attr(&document, ".communityName a", "href")
+            .and_then(|href| community_id(&href))
+            .or_else(|| script_value(&scripts, "resblockId"));
+
+        let agent = select_text(&document, ".brokerName .name")
+            .or_else(|| select_text(&document, ".ke-agent-sj-name"))
+            .map(|name| Agent {
+                name,
+                phone: select_text(&document, ".brokerInfo .phone")
+                    .or_else(|| select_text(&document, ".ke-agent-sj-phone")),
+            });
+
+        ErshoufangDetail {
+            listing_id,
+            community_id,
+            coordinates: coordinates(&scripts),
+            agent,
+            orientation: base("房屋朝向"),
+            structure: base("户型结构"),
+            building_structure: base("建筑结构"),
+            elevator_ratio: base("梯户比例"),
+            has_elevator: base("配备电梯").and_then(|text| yes_no(&text)),
+            property_right_years: base("产权年限").and_then(|text| parse_integer(&text)),
+            ownership: transaction("交易权属"),
+            listed_on: transaction("挂牌时间").and_then(|text| parse_date(&text)),
+            last_transaction: transaction("上次交易").and_then(|text| parse_date(&text)),
+            holding_period: transaction("房屋年限"),
+            mortgaged: transaction("抵押信息").map(|text| !text.starts_with("无抵押")),
+            base_attributes,
+            transaction_attributes,
+        }
+    }
+}
+
+impl DetailScraper for ErshoufangDetailScraper {
+    type Summary = ErshoufangData;
+    type Detail = ErshoufangDetail;
+
+    fn options(&self) -> &DetailOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut DetailOptions {
+        &mut self.options
+    }
+
+    fn summary_url(summary: &ErshoufangData) -> &str {
+        &summary.url
+    }
+
+    async fn scrape_detail(&self, url: &str) -> Result<ErshoufangDetail, ScrapeError> {
+        self.scrape_ershoufang_detail(url).await
+    }
+
+    /// 详情页上的房源编号与列表记录不一致时（如被重定向到了其他房源）不合并
+    fn check_detail(&self, summary: &ErshoufangData, detail: &ErshoufangDetail) -> Result<(), ScrapeError> {
+        check_id("listing_id", "房源编号", summary.id.as_ref(), detail.listing_id.as_ref())
+    }
+}
+
+/// 页面脚本中的 `resblockPosition: '106.523871,29.622413'`，经度在前
+fn coordinates(scripts: &str) -> Option<Coordinates> {
+    let value = script_value(scripts, "resblockPosition")?;
+    let (longitude, latitude) = value.split_once(',')?;
+    Some(Coordinates {
+        longitude: longitude.trim().parse().ok()?,
+        latitude: latitude.trim().parse().ok()?,
+    })
+}
+
+/// 页面脚本中 `key: 'value'` 或 `"key":"value"` 形式的值
+///
+/// 只匹配完整的键：前面不能紧跟标识符字符（排除 `oldResblockId`），
+/// 后面在可选的引号和空白之后必须是 `:` 或 `=`（排除 `resblockIdList`）。
+fn script_value(scripts: &str, key: &str) -> Option<String> {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$';
+    scripts.match_indices(key).find_map(|(start, _)| {
+        if scripts[..start].chars().next_back().is_some_and(is_ident) {
+            return None;
+        }
+        let rest = scripts[start + key.len()..].trim_start_matches(['"', '\'']).trim_start();
+        let rest = rest.strip_prefix([':', '='])?.trim_start().trim_start_matches(['"', '\'']);
+        let value: String = rest
+            .chars()
+            .take_while(|c| !matches!(c, '"' | '\'' | '}' | '\n') && *c != ';')
+            .collect();
+        let value = value.trim().trim_end_matches(',').trim().to_string();
+        (!value.is_empty()).then_some(value)
+    })
+}
+
+fn select_text(document: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).unwrap();
+    document.select(&selector)
+        .next()
+        .map(|el| el.text().collect::<String>().trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+fn select_attr(document: &Html, selector: &str, attr: &str) -> Option<String> {
+    let selector = Selector::parse(selector).unwrap();
+    document.select(&selector)
+        .next()
+        .and_then(|el| el.value().attr(attr))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_value_matches_whole_keys() {
+        let scripts = r#"var oldResblockId = '1'; var c = { resblockIdList: ['2'], "resblockId" : "3611056497876", houseCode: 106118000001 };"#;
+        assert_eq!(script_value(scripts, "resblockId").as_deref(), Some("3611056497876"));
+        assert_eq!(script_value(scripts, "houseCode").as_deref(), Some("106118000001"));
+        assert_eq!(script_value("resblockIdList: ['2']", "resblockId"), None);
+        assert_eq!(script_value("window.resblockId='3611056497876';", "resblockId").as_deref(), Some("3611056497876"));
+    }
+}
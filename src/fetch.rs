@@ -0,0 +1,202 @@
+use crate::archive::{Archive, ArchivedPage};
+use crate::error::ScrapeError;
+use crate::ratelimit::{RateLimit, RateLimiter};
+use reqwest::header::{HeaderMap, CONTENT_ENCODING, CONTENT_TYPE, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Url};
+use std::io::Read;
+use std::sync::Arc;
+
+const USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+/// 所有页面类型共用的抓取层：持有 HTTP 客户端和 Cookie，负责限速、请求头、解压、解码和人机验证检测
+///
+/// 克隆出来的 Fetcher 共用同一个连接池和限速器，只有限速配置各自独立。
+#[derive(Clone)]
+pub struct Fetcher {
+    client: Client,
+    cookies: String,
+    limiter: Arc<RateLimiter>,
+    rate_limit: RateLimit,
+    archive: Option<Archive>,
+}
+
+impl Fetcher {
+    pub fn new(cookies: String) -> Self {
+        // 跳转到登录页或验证页时不再跟随，由 fetch 根据 Location 返回对应的错误
+        let redirect_policy = Policy::custom(|attempt| {
+            if is_login_url(attempt.url()) || is_captcha_url(attempt.url()) {
+                attempt.stop()
+            } else if attempt.previous().len() >= 10 {
+                attempt.error("重定向次数过多")
+            } else {
+                attempt.follow()
+            }
+        });
+
+        let client = Client::builder()
+            .user_agent(USER_AGENT)
+            .redirect(redirect_policy)
+            .build()
+            .unwrap();
+
+        Self {
+            client,
+            cookies,
+            limiter: Arc::new(RateLimiter::default()),
+            rate_limit: RateLimit::default(),
+            archive: None,
+        }
+    }
+
+    /// 设置本 Fetcher 发出请求时使用的限速配置
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// 把每个成功返回的页面保存到归档中，便于之后离线重新解析
+    pub fn with_archive(mut self, archive: Archive) -> Self {
+        self.archive = Some(archive);
+        self
+    }
+
+    /// 抓取 `url` 并返回解码后的 HTML
+    pub async fn fetch(&self, url: &str) -> Result<String, ScrapeError> {
+        let parsed_url = Url::parse(url).map_err(|e| ScrapeError::InvalidUrl { url: url.to_string(), reason: e.to_string() })?;
+        let host = parsed_url.host_str().unwrap_or_default();
+        // 非默认端口时 Host 头需要带上端口，如本地模拟服务器
+        let authority = match parsed_url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+
+        self.limiter.acquire(&authority, &self.rate_limit).await;
+
+        let response = self.client
+            .get(url)
+            .header("Cookie", &self.cookies)
+            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
+            .header("Accept-Language", "zh-CN,zh;q=0.9,en;q=0.8")
+            .header("Accept-Encoding", "gzip, deflate, br")
+            .header("Connection", "keep-alive")
+            .header("User-Agent", USER_AGENT)
+            .header("Host", &authority)
+            .header("Referer", format!("{}://{}/", parsed_url.scheme(), authority))
+            .send()
+            .await?;
+
+        // 检查响应状态
+        let status = response.status();
+        if status.is_redirection() {
+            let location = response.headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string();
+            // 普通跳转已由客户端跟随，到这里的是被拦下的登录/验证跳转，或没有 Location 的 3xx
+            let target = parsed_url.join(&location).ok();
+            if target.as_ref().is_some_and(is_captcha_url) {
+                return Err(ScrapeError::Captcha);
+            }
+            if target.as_ref().is_some_and(is_login_url) {
+                return Err(ScrapeError::LoginRequired { location });
+            }
+            return Err(ScrapeError::Http(status));
+        }
+        if !status.is_success() {
+            return Err(ScrapeError::Http(status));
+        }
+
+        let headers = response.headers().clone();
+        let bytes = response.bytes().await?;
+        let html_content = decode_body(&headers, &bytes)?;
+
+        // 人机验证页面也一并归档，方便排查
+        if let Some(archive) = &self.archive {
+            let header_map = headers
+                .iter()
+                .filter_map(|(name, value)| value.to_str().ok().map(|v| (name.to_string(), v.to_string())))
+                .collect();
+            let page = ArchivedPage::new(url, status.as_u16(), header_map, &html_content);
+            if let Err(e) = archive.store(&page) {
+                println!("保存归档失败: {:#}", e);
+            }
+        }
+
+        // 检查是否遇到人机验证
+        if html_content.contains("人机验证") || html_content.contains("CAPTCHA") {
+            return Err(ScrapeError::Captcha);
+        }
+
+        Ok(html_content)
+    }
+}
+
+/// 按 Content-Encoding 解压响应体并解码为 UTF-8
+///
+/// 我们手动设置了 Accept-Encoding，reqwest 不会自动解压，所以 gzip/deflate/br 都要在这里处理。
+/// 部分响应不带 Content-Encoding 头但内容仍是 gzip，因此额外按魔数嗅探一次。
+fn decode_body(headers: &HeaderMap, bytes: &[u8]) -> Result<String, ScrapeError> {
+    let encoding = headers
+        .get(CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    let is_gzip_magic = bytes.len() > 2 && bytes[0] == 0x1f && bytes[1] == 0x8b;
+
+    let decompressed = match encoding.as_str() {
+        "br" => {
+            let mut decoded = Vec::new();
+            brotli::Decompressor::new(bytes, 4096)
+                .read_to_end(&mut decoded)
+                .map_err(|e| ScrapeError::Decode(format!("brotli解压失败: {}", e)))?;
+            decoded
+        }
+        "deflate" => {
+            let mut decoded = Vec::new();
+            flate2::read::ZlibDecoder::new(bytes)
+                .read_to_end(&mut decoded)
+                .map_err(|e| ScrapeError::Decode(format!("deflate解压失败: {}", e)))?;
+            decoded
+        }
+        "gzip" | "x-gzip" => gunzip(bytes)?,
+        _ if is_gzip_magic => gunzip(bytes)?,
+        _ => bytes.to_vec(),
+    };
+
+    String::from_utf8(decompressed).map_err(|e| {
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("unknown");
+        ScrapeError::Decode(format!("UTF-8解码失败 (内容类型: {}): {}", content_type, e))
+    })
+}
+
+/// 登录页：`passport.`/`login.` 开头的站点或 `/login` 路径，如 `https://clogin.ke.com/login?service=...`
+///
+/// 只看站点和路径，查询参数里带 login 的普通地址不算。
+fn is_login_url(url: &Url) -> bool {
+    let host = url.host_str().unwrap_or_default();
+    host.starts_with("passport.")
+        || host.starts_with("login.")
+        || url.path().split('/').any(|part| part == "login" || part.starts_with("login."))
+}
+
+/// 人机验证页：`hip.` 开头的站点或路径中带 captcha，如 `https://hip.ke.com/captcha?...`
+///
+/// 与登录页一样只看站点和路径，查询参数里带 captcha 的普通地址不算。
+fn is_captcha_url(url: &Url) -> bool {
+    let host = url.host_str().unwrap_or_default();
+    host.starts_with("hip.") || host.contains("captcha") || url.path().contains("captcha")
+}
+
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, ScrapeError> {
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(bytes)
+        .read_to_end(&mut decoded)
+        .map_err(|e| ScrapeError::Decode(format!("gzip解压失败: {}", e)))?;
+    Ok(decoded)
+}
@@ -0,0 +1,463 @@
+use crate::search_url::{FilterSegment, SearchUrl};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 贝壳网列表页的筛选条件
+///
+/// 序列化成路径段语法，如"3室、80-100平、精装"对应 `l3ba80ea100de1`，
+/// 也可以从已有的列表页地址解析回来。同一类条件可以多选，贝壳网按"或"处理。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchFilter {
+    /// 装修情况
+    pub decoration: Vec<Decoration>,
+    /// 居室数，6 表示五室以上
+    pub rooms: Vec<u32>,
+    /// 建筑面积（平米）
+    pub area: Option<Range>,
+    /// 总价（万）
+    pub total_price: Option<Range>,
+    /// 楼龄
+    pub building_age: Option<BuildingAge>,
+    /// 是否有电梯
+    pub elevator: Option<bool>,
+    /// 楼层
+    pub floor: Vec<FloorLevel>,
+    /// 朝向
+    pub orientation: Vec<Orientation>,
+    /// 没有建模的筛选段（如固定的面积/价格档位），原样保留
+    pub extra: Vec<FilterSegment>,
+}
+
+/// 闭区间，两端都可以省略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Decoration {
+    #[serde(rename = "精装", alias = "jingzhuang")]
+    Jingzhuang,
+    #[serde(rename = "简装", alias = "jianzhuang")]
+    Jianzhuang,
+    #[serde(rename = "毛坯", alias = "maopi")]
+    Maopi,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BuildingAge {
+    #[serde(rename = "5年以内", alias = "within5")]
+    Within5,
+    #[serde(rename = "10年以内", alias = "within10")]
+    Within10,
+    #[serde(rename = "15年以内", alias = "within15")]
+    Within15,
+    #[serde(rename = "20年以内", alias = "within20")]
+    Within20,
+    #[serde(rename = "20年以上", alias = "over20")]
+    Over20,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FloorLevel {
+    #[serde(rename = "低楼层", alias = "low")]
+    Low,
+    #[serde(rename = "中楼层", alias = "middle")]
+    Middle,
+    #[serde(rename = "高楼层", alias = "high")]
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Orientation {
+    #[serde(rename = "朝东", alias = "east")]
+    East,
+    #[serde(rename = "朝南", alias = "south")]
+    South,
+    #[serde(rename = "朝西", alias = "west")]
+    West,
+    #[serde(rename = "朝北", alias = "north")]
+    North,
+    #[serde(rename = "南北", alias = "north_south")]
+    NorthSouth,
+}
+
+impl SearchFilter {
+    /// 按贝壳网的路径段语法展开成筛选段
+    pub fn segments(&self) -> Vec<FilterSegment> {
+        let mut segments = Vec::new();
+
+        segments.extend(self.rooms.iter().map(|rooms| FilterSegment::Layout(*rooms)));
+        if let Some(area) = self.area {
+            segments.push(FilterSegment::AreaRange { min: area.min, max: area.max });
+        }
+        if let Some(price) = self.total_price {
+            segments.push(FilterSegment::PriceRange { min: price.min, max: price.max });
+        }
+        segments.extend(self.orientation.iter().map(|o| FilterSegment::Orientation(o.code())));
+        if let Some(age) = self.building_age {
+            segments.push(FilterSegment::BuildingAge(age.code()));
+        }
+        segments.extend(self.floor.iter().map(|f| FilterSegment::FloorLevel(f.code())));
+        if let Some(elevator) = self.elevator {
+            segments.push(FilterSegment::Elevator(if elevator { 2 } else { 1 }));
+        }
+        segments.extend(self.decoration.iter().map(|d| FilterSegment::Decoration(d.code())));
+        segments.extend(self.extra.iter().cloned());
+
+        segments
+    }
+
+    /// 从筛选段还原筛选条件，无法识别的段放进 `extra`
+    pub fn from_segments(segments: &[FilterSegment]) -> Self {
+        let mut filter = SearchFilter::default();
+
+        for segment in segments {
+            let recognized = match segment {
+                FilterSegment::Decoration(code) => Decoration::from_code(*code).map(|d| filter.decoration.push(d)),
+                FilterSegment::Layout(rooms) => {
+                    filter.rooms.push(*rooms);
+                    Some(())
+                }
+                FilterSegment::AreaRange { min, max } => {
+                    filter.area = Some(Range { min: *min, max: *max });
+                    Some(())
+                }
+                FilterSegment::PriceRange { min, max } => {
+                    filter.total_price = Some(Range { min: *min, max: *max });
+                    Some(())
+                }
+                FilterSegment::Orientation(code) => Orientation::from_code(*code).map(|o| filter.orientation.push(o)),
+                FilterSegment::BuildingAge(code) => BuildingAge::from_code(*code).map(|a| filter.building_age = Some(a)),
+                FilterSegment::FloorLevel(code) => FloorLevel::from_code(*code).map(|f| filter.floor.push(f)),
+                FilterSegment::Elevator(code @ (1 | 2)) => {
+                    filter.elevator = Some(*code == 2);
+                    Some(())
+                }
+                _ => None,
+            };
+
+            if recognized.is_none() {
+                filter.extra.push(segment.clone());
+            }
+        }
+
+        filter
+    }
+
+    /// 从列表页地址解析筛选条件，如 `https://cq.ke.com/chengjiao/dazhulin/pg1l3de1/`
+    pub fn from_url(url: &str) -> Result<Self> {
+        let search = SearchUrl::parse(url)?;
+        Ok(Self::from_segments(&search.filters))
+    }
+
+    /// 把另一个筛选条件合并进来，同类的多选条件取并集，单选条件以 `other` 为准
+    pub fn merge(mut self, other: SearchFilter) -> Self {
+        fn extend_unique<T: PartialEq>(target: &mut Vec<T>, items: Vec<T>) {
+            for item in items {
+                if !target.contains(&item) {
+                    target.push(item);
+                }
+            }
+        }
+
+        extend_unique(&mut self.decoration, other.decoration);
+        extend_unique(&mut self.rooms, other.rooms);
+        extend_unique(&mut self.floor, other.floor);
+        extend_unique(&mut self.orientation, other.orientation);
+        extend_unique(&mut self.extra, other.extra);
+        self.area = other.area.or(self.area);
+        self.total_price = other.total_price.or(self.total_price);
+        self.building_age = other.building_age.or(self.building_age);
+        self.elevator = other.elevator.or(self.elevator);
+        self
+    }
+
+    /// 便于阅读的中文描述，如"3室、80-100平、精装"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+
+        if !self.rooms.is_empty() {
+            let rooms: Vec<String> = self.rooms.iter()
+                .map(|r| if *r >= 6 { "5室以上".to_string() } else { format!("{}室", r) })
+                .collect();
+            parts.push(rooms.join("/"));
+        }
+        if let Some(area) = self.area {
+            parts.push(format!("{}平", area));
+        }
+        if let Some(price) = self.total_price {
+            parts.push(format!("{}万", price));
+        }
+        if !self.orientation.is_empty() {
+            parts.push(self.orientation.iter().map(|o| o.label()).collect::<Vec<_>>().join("/"));
+        }
+        if let Some(age) = self.building_age {
+            parts.push(age.label().to_string());
+        }
+        if !self.floor.is_empty() {
+            parts.push(self.floor.iter().map(|f| f.label()).collect::<Vec<_>>().join("/"));
+        }
+        if let Some(elevator) = self.elevator {
+            parts.push(if elevator { "有电梯" } else { "无电梯" }.to_string());
+        }
+        if !self.decoration.is_empty() {
+            parts.push(self.decoration.iter().map(|d| d.label()).collect::<Vec<_>>().join("/"));
+        }
+        parts.extend(self.extra.iter().map(|s| s.to_string()));
+
+        if parts.is_empty() {
+            "不限".to_string()
+        } else {
+            parts.join("、")
+        }
+    }
+}
+
+impl fmt::Display for SearchFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in self.segments() {
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SearchFilter {
+    type Err = anyhow::Error;
+
+    /// 解析路径段语法，如 `l3ba80ea100de1`
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Self::from_segments(&FilterSegment::parse_all(s)?))
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => write!(f, "{}-{}", min, max),
+            (Some(min), None) => write!(f, "≥{}", min),
+            (None, Some(max)) => write!(f, "≤{}", max),
+            (None, None) => write!(f, "不限"),
+        }
+    }
+}
+
+impl FromStr for Range {
+    type Err = anyhow::Error;
+
+    /// 解析 `80-100`、`80-`、`-100` 形式的区间
+    fn from_str(s: &str) -> Result<Self> {
+        let (min, max) = s
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("区间格式应为 min-max: {}", s))?;
+        let parse = |v: &str| -> Result<Option<u32>> {
+            let v = v.trim();
+            if v.is_empty() { Ok(None) } else { Ok(Some(v.parse()?)) }
+        };
+        Ok(Range { min: parse(min)?, max: parse(max)? })
+    }
+}
+
+impl Decoration {
+    fn code(&self) -> u32 {
+        match self {
+            Decoration::Jingzhuang => 1,
+            Decoration::Jianzhuang => 2,
+            Decoration::Maopi => 3,
+        }
+    }
+
+    fn from_code(code: u32) -> Option<Self> {
+        match code {
+            1 => Some(Decoration::Jingzhuang),
+            2 => Some(Decoration::Jianzhuang),
+            3 => Some(Decoration::Maopi),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Decoration::Jingzhuang => "精装",
+            Decoration::Jianzhuang => "简装",
+            Decoration::Maopi => "毛坯",
+        }
+    }
+}
+
+impl FromStr for Decoration {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "精装" | "jingzhuang" => Ok(Decoration::Jingzhuang),
+            "简装" | "jianzhuang" => Ok(Decoration::Jianzhuang),
+            "毛坯" | "maopi" => Ok(Decoration::Maopi),
+            _ => Err(anyhow::anyhow!("未知的装修情况: {}", s)),
+        }
+    }
+}
+
+impl BuildingAge {
+    fn code(&self) -> u32 {
+        match self {
+            BuildingAge::Within5 => 1,
+            BuildingAge::Within10 => 2,
+            BuildingAge::Within15 => 3,
+            BuildingAge::Within20 => 4,
+            BuildingAge::Over20 => 5,
+        }
+    }
+
+    fn from_code(code: u32) -> Option<Self> {
+        match code {
+            1 => Some(BuildingAge::Within5),
+            2 => Some(BuildingAge::Within10),
+            3 => Some(BuildingAge::Within15),
+            4 => Some(BuildingAge::Within20),
+            5 => Some(BuildingAge::Over20),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BuildingAge::Within5 => "5年以内",
+            BuildingAge::Within10 => "10年以内",
+            BuildingAge::Within15 => "15年以内",
+            BuildingAge::Within20 => "20年以内",
+            BuildingAge::Over20 => "20年以上",
+        }
+    }
+}
+
+impl FloorLevel {
+    fn code(&self) -> u32 {
+        match self {
+            FloorLevel::Low => 1,
+            FloorLevel::Middle => 2,
+            FloorLevel::High => 3,
+        }
+    }
+
+    fn from_code(code: u32) -> Option<Self> {
+        match code {
+            1 => Some(FloorLevel::Low),
+            2 => Some(FloorLevel::Middle),
+            3 => Some(FloorLevel::High),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FloorLevel::Low => "低楼层",
+            FloorLevel::Middle => "中楼层",
+            FloorLevel::High => "高楼层",
+        }
+    }
+}
+
+impl Orientation {
+    fn code(&self) -> u32 {
+        match self {
+            Orientation::East => 1,
+            Orientation::South => 2,
+            Orientation::West => 3,
+            Orientation::North => 4,
+            Orientation::NorthSouth => 5,
+        }
+    }
+
+    fn from_code(code: u32) -> Option<Self> {
+        match code {
+            1 => Some(Orientation::East),
+            2 => Some(Orientation::South),
+            3 => Some(Orientation::West),
+            4 => Some(Orientation::North),
+            5 => Some(Orientation::NorthSouth),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Orientation::East => "朝东",
+            Orientation::South => "朝南",
+            Orientation::West => "朝西",
+            Orientation::North => "朝北",
+            Orientation::NorthSouth => "南北",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(filter: &SearchFilter) -> SearchFilter {
+        SearchFilter::from_segments(&filter.segments())
+    }
+
+    #[test]
+    fn every_dimension_round_trips_through_segments() {
+        let filters = [
+            SearchFilter { decoration: vec![Decoration::Jingzhuang, Decoration::Maopi], ..Default::default() },
+            SearchFilter { rooms: vec![2, 3, 6], ..Default::default() },
+            SearchFilter { area: Some(Range { min: Some(80), max: Some(100) }), ..Default::default() },
+            SearchFilter { area: Some(Range { min: Some(120), max: None }), ..Default::default() },
+            SearchFilter { total_price: Some(Range { min: None, max: Some(150) }), ..Default::default() },
+            SearchFilter { total_price: Some(Range { min: Some(100), max: Some(200) }), ..Default::default() },
+            SearchFilter { building_age: Some(BuildingAge::Within10), ..Default::default() },
+            SearchFilter { elevator: Some(true), ..Default::default() },
+            SearchFilter { elevator: Some(false), ..Default::default() },
+            SearchFilter { floor: vec![FloorLevel::Low, FloorLevel::High], ..Default::default() },
+            SearchFilter { orientation: vec![Orientation::South, Orientation::NorthSouth], ..Default::default() },
+            SearchFilter { extra: vec![FilterSegment::AreaBand(3), FilterSegment::PriceBand(4)], ..Default::default() },
+        ];
+        for filter in &filters {
+            assert_eq!(&round_trip(filter), filter, "{}", filter);
+        }
+    }
+
+    #[test]
+    fn combined_filter_round_trips_through_path_syntax() {
+        let filter = SearchFilter {
+            decoration: vec![Decoration::Jianzhuang],
+            rooms: vec![3],
+            area: Some(Range { min: Some(80), max: Some(100) }),
+            total_price: Some(Range { min: Some(100), max: None }),
+            building_age: Some(BuildingAge::Over20),
+            elevator: Some(true),
+            floor: vec![FloorLevel::Middle],
+            orientation: vec![Orientation::East, Orientation::West],
+            extra: vec![FilterSegment::Other { prefix: "sf".to_string(), value: 1 }],
+        };
+        assert_eq!(filter.to_string(), "l3ba80ea100bp100f1f3y5lc2ie2de2sf1");
+        assert_eq!(round_trip(&filter), filter);
+        assert_eq!(filter.to_string().parse::<SearchFilter>().unwrap(), filter);
+    }
+
+    #[test]
+    fn unknown_codes_are_kept_as_extra() {
+        let filter: SearchFilter = "de9ie3l2".parse().unwrap();
+        assert_eq!(filter.rooms, [2]);
+        assert_eq!(filter.decoration, []);
+        assert_eq!(filter.elevator, None);
+        assert_eq!(filter.extra, [FilterSegment::Decoration(9), FilterSegment::Elevator(3)]);
+        assert_eq!(round_trip(&filter), filter);
+    }
+
+    #[test]
+    fn from_url_reads_multiple_orientations() {
+        let filter = SearchFilter::from_url("https://cq.ke.com/ershoufang/dazhulin/pg2f2f5l3/").unwrap();
+        assert_eq!(filter.orientation, [Orientation::South, Orientation::NorthSouth]);
+        assert_eq!(filter.rooms, [3]);
+        assert_eq!(filter.describe(), "3室、朝南/南北");
+    }
+}
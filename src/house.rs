@@ -0,0 +1,75 @@
+use crate::text::parse_integer;
+use serde::{Deserialize, Serialize};
+
+/// 房源所在楼层的位置
+///
+/// 与筛选条件里的 [`crate::filter::FloorLevel`] 不同，列表页上还会出现地下室，没有对应的筛选段。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FloorPosition {
+    #[serde(rename = "低楼层")]
+    Low,
+    #[serde(rename = "中楼层")]
+    Middle,
+    #[serde(rename = "高楼层")]
+    High,
+    #[serde(rename = "地下室")]
+    Basement,
+}
+
+impl FloorPosition {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FloorPosition::Low => "低楼层",
+            FloorPosition::Middle => "中楼层",
+            FloorPosition::High => "高楼层",
+            FloorPosition::Basement => "地下室",
+        }
+    }
+}
+
+/// 楼层信息，如 "中楼层(共18层)"、"低楼层   (共33层)"、"共6层"，租房列表上是 "中楼层（18层）"
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Floor {
+    pub position: Option<FloorPosition>,
+    /// 楼栋总层数
+    pub total_floors: Option<u32>,
+}
+
+impl Floor {
+    pub fn parse(text: &str) -> Self {
+        let position = if text.contains("地下") {
+            Some(FloorPosition::Basement)
+        } else if text.contains("低楼层") || text.contains("底层") {
+            Some(FloorPosition::Low)
+        } else if text.contains("中楼层") {
+            Some(FloorPosition::Middle)
+        } else if text.contains("高楼层") || text.contains("顶层") {
+            Some(FloorPosition::High)
+        } else {
+            None
+        };
+
+        let total_floors = text.find('共')
+            .or_else(|| text.find(['(', '（']))
+            .and_then(|pos| parse_integer(&text[pos..]));
+
+        Floor { position, total_floors }
+    }
+
+    /// 如 "中楼层/共18层"，缺失的部分省略
+    pub fn describe(&self) -> String {
+        match (self.position, self.total_floors) {
+            (Some(position), Some(total)) => format!("{}/共{}层", position.label(), total),
+            (Some(position), None) => position.label().to_string(),
+            (None, Some(total)) => format!("共{}层", total),
+            (None, None) => "未知".to_string(),
+        }
+    }
+}
+
+/// 取出 "2015年建"、"中楼层(共18层) 2015年建板楼" 中的建成年份
+pub fn parse_build_year(text: &str) -> Option<u32> {
+    let end = text.find("年建").or_else(|| text.find('年'))?;
+    let digits: String = text[..end].chars().rev().take_while(|c| c.is_ascii_digit()).collect();
+    digits.chars().rev().collect::<String>().parse().ok()
+}
@@ -0,0 +1,676 @@
+use crate::checkpoint::Checkpoint;
+use crate::chengjiao::{ChengjiaoData, ChengjiaoScraper};
+use crate::chengjiao_detail::ChengjiaoDetailScraper;
+use crate::config::JobConfig;
+use crate::crawl::{crawl, FailedPage, ListScraper};
+use crate::detail::{DetailRecord, DetailScraper, FailedDetail};
+use crate::ershoufang::{ErshoufangData, ErshoufangScraper};
+use crate::ershoufang_detail::ErshoufangDetailScraper;
+use crate::fetch::Fetcher;
+use crate::loupan::{LoupanData, LoupanScraper};
+use crate::loupan_detail::LoupanDetailScraper;
+use crate::output::{
+    save_chengjiao_detail_to_csv, save_chengjiao_to_csv, save_ershoufang_detail_to_csv, save_ershoufang_to_csv,
+    save_loupan_detail_to_csv, save_loupan_to_csv, save_outputs, save_xiaoqu_detail_to_csv, save_xiaoqu_to_csv,
+    save_zufang_to_csv, CsvWriter,
+};
+use crate::price_history::PriceHistory;
+use crate::search_url::{ListingKind, SearchUrl};
+use crate::text::or_unknown;
+use crate::xiaoqu::{XiaoquData, XiaoquScraper};
+use crate::xiaoqu_detail::XiaoquDetailScraper;
+use crate::zufang::{RentalType, ZufangData, ZufangScraper};
+use anyhow::Result;
+use chrono::Local;
+use serde::Serialize;
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+
+/// 单个任务的执行结果，用于最后的总结报告
+#[derive(Debug)]
+pub struct JobSummary {
+    pub name: String,
+    pub kind: ListingKind,
+    pub url: String,
+    pub records: usize,
+    /// 重试后仍然失败的页
+    pub failed_pages: Vec<FailedPage>,
+    /// 重试后仍然失败的详情页
+    pub failed_details: Vec<FailedDetail>,
+    pub error: Option<String>,
+}
+
+/// 执行单个任务
+///
+/// `resume` 为 true 时从上一次运行留下的断点继续，只爬取尚未完成或失败的页。
+pub async fn run_job(job: &JobConfig, fetcher: &Fetcher, default_output_dir: &Path, resume: bool) -> Result<JobSummary> {
+    let output_dir = job.output_dir.as_deref().unwrap_or(default_output_dir);
+    fs::create_dir_all(output_dir)?;
+    let mut checkpoint = Checkpoint::open(output_dir, &job.output_stem(), resume)?;
+
+    let search = job.search_url();
+
+    println!("任务: {} ({}数据)", job.name, job.kind.label());
+    println!("筛选条件: {}", job.search_filter().describe());
+    println!("目标URL: {}", search);
+    match job.end_page {
+        Some(end_page) => println!("开始爬取第 {} 页到第 {} 页...", job.start_page, end_page),
+        None => println!("从第 {} 页开始爬取，页数根据结果总数自动确定...", job.start_page),
+    }
+
+    let fetcher = fetcher.clone().with_rate_limit(job.rate_limit.clone());
+    let outcome = match job.kind {
+        ListingKind::Chengjiao => {
+            let scraper = ChengjiaoScraper::new(fetcher.clone())
+                .with_retry_policy(job.retry.clone())
+                .with_concurrency(job.concurrency);
+            crawl_list(
+                job,
+                &search,
+                &mut checkpoint,
+                scraper,
+                |items| report_chengjiao(job, output_dir, items),
+                |items| scrape_details(job, output_dir, ChengjiaoDetailScraper::new(fetcher), items, save_chengjiao_detail_to_csv),
+            )
+            .await
+        }
+        ListingKind::Ershoufang => {
+            let scraper = ErshoufangScraper::new(fetcher.clone())
+                .with_retry_policy(job.retry.clone())
+                .with_concurrency(job.concurrency);
+            crawl_list(
+                job,
+                &search,
+                &mut checkpoint,
+                scraper,
+                |items| report_ershoufang(job, output_dir, items),
+                |items| scrape_details(job, output_dir, ErshoufangDetailScraper::new(fetcher), items, save_ershoufang_detail_to_csv),
+            )
+            .await
+        }
+        ListingKind::Xiaoqu => {
+            let scraper = XiaoquScraper::new(fetcher.clone())
+                .with_retry_policy(job.retry.clone())
+                .with_concurrency(job.concurrency);
+            crawl_list(
+                job,
+                &search,
+                &mut checkpoint,
+                scraper,
+                |items| report_xiaoqu(job, output_dir, items),
+                |items| scrape_details(job, output_dir, XiaoquDetailScraper::new(fetcher), items, save_xiaoqu_detail_to_csv),
+            )
+            .await
+        }
+        ListingKind::Zufang => {
+            let scraper = ZufangScraper::new(fetcher)
+                .with_retry_policy(job.retry.clone())
+                .with_concurrency(job.concurrency);
+            crawl_list(
+                job,
+                &search,
+                &mut checkpoint,
+                scraper,
+                |items| report_zufang(job, output_dir, items),
+                // 租房没有详情页爬虫，details = true 已在校验任务配置时被拒绝
+                |_| async { Ok(Vec::new()) },
+            )
+            .await
+        }
+        ListingKind::Loupan => {
+            let scraper = LoupanScraper::new(fetcher.clone())
+                .with_retry_policy(job.retry.clone())
+                .with_concurrency(job.concurrency);
+            crawl_list(
+                job,
+                &search,
+                &mut checkpoint,
+                scraper,
+                |items| report_loupan(job, output_dir, items),
+                |items| scrape_details(job, output_dir, LoupanDetailScraper::new(fetcher), items, save_loupan_detail_to_csv),
+            )
+            .await
+        }
+    };
+
+    let (records, failed_pages, failed_details, error) = match outcome {
+        Ok((records, failed_pages, failed_details)) => {
+            checkpoint.finish(!failed_pages.is_empty())?;
+            (records, failed_pages, failed_details, None)
+        }
+        Err(e) => {
+            println!("{}数据爬取失败: {}", job.kind.label(), e);
+            println!("已完成的页保存在断点中，可以使用 --resume 继续");
+            (0, Vec::new(), Vec::new(), Some(e.to_string()))
+        }
+    };
+
+    Ok(JobSummary {
+        name: job.name.clone(),
+        kind: job.kind,
+        url: search.to_string(),
+        records,
+        failed_pages,
+        failed_details,
+        error,
+    })
+}
+
+pub fn print_summary(summaries: &[JobSummary]) {
+    println!("\n=== 爬取总结报告 ===");
+    for summary in summaries {
+        match &summary.error {
+            None => println!("{} [{}数据]: {} 条", summary.name, summary.kind.label(), summary.records),
+            Some(e) => println!("{} [{}数据]: 失败 - {} ({})", summary.name, summary.kind.label(), e, summary.url),
+        }
+        for failed in &summary.failed_pages {
+            println!("  失败页: {}", failed.describe());
+        }
+        for failed in &summary.failed_details {
+            println!("  失败详情页: {} ({})", failed.url, failed.error);
+        }
+    }
+
+    // 成交和在售总是列出，其他类型有数据时才列出
+    let mut total = 0;
+    for kind in ListingKind::ALL {
+        let kind_total: usize = summaries.iter()
+            .filter(|s| s.kind == kind)
+            .map(|s| s.records)
+            .sum();
+        if kind_total > 0 || matches!(kind, ListingKind::Chengjiao | ListingKind::Ershoufang) {
+            println!("{}数据: {} 条", kind.label(), kind_total);
+        }
+        total += kind_total;
+    }
+    println!("总数据量: {} 条", total);
+
+    if total == 0 {
+        println!("\n警告：未获取到任何数据，请检查网络连接或网站结构是否发生变化");
+    } else {
+        println!("\n所有数据爬取完成！");
+    }
+}
+
+fn report_chengjiao(job: &JobConfig, output_dir: &Path, results: Vec<ChengjiaoData>) -> Result<Vec<ChengjiaoData>> {
+    println!("\n成交数据爬取完成！总共获取 {} 条数据", results.len());
+
+    if results.is_empty() {
+        println!("未获取到任何成交数据，可能是页面结构发生变化或遇到反爬虫机制");
+        return Ok(results);
+    }
+
+    // 打印前几条数据作为预览
+    println!("\n=== 成交数据预览 ===");
+    for (i, data) in results.iter().take(3).enumerate() {
+        println!("\n--- 第 {} 条数据 ---", i + 1);
+        println!("编号: {}", or_unknown(data.id.as_ref(), ""));
+        println!("标题: {}", data.title);
+        println!("成交价: {}", or_unknown(data.deal_price, "万"));
+        println!("挂牌价: {}", or_unknown(data.list_price, "万"));
+        println!("让价: {} ({})", or_unknown(data.discount, "万"), or_unknown(data.discount_percent, "%"));
+        println!("单价: {}", or_unknown(data.unit_price, "元/平"));
+        println!("成交日期: {}", or_unknown(data.deal_date, ""));
+        println!("装修情况: {}", data.is_renovated);
+        println!("成交周期: {}", or_unknown(data.deal_cycle_days, "天"));
+        println!("朝向: {}", data.orientation);
+        println!("楼层: {}", data.floor.describe());
+        println!("建成年份: {} {}", or_unknown(data.build_year, "年"), data.building_type);
+    }
+
+    if results.len() > 3 {
+        println!("\n... 还有 {} 条数据", results.len() - 3);
+    }
+
+    // 按任务配置的格式保存成交数据
+    save_outputs(&results, output_dir, &job.output_stem(), &job.outputs, "成交数据", save_chengjiao_to_csv)?;
+
+    // 成交数据统计信息
+    print_chengjiao_statistics(&results);
+
+    Ok(results)
+}
+
+/// 爬取列表页并输出结果，任务要求时继续爬取详情页，返回记录数、失败的页和失败的详情页
+///
+/// `report` 打印预览、保存输出并返回要继续爬取详情页的记录，`details` 爬取这些记录的详情页。
+async fn crawl_list<S, R, D, F>(
+    job: &JobConfig,
+    search: &SearchUrl,
+    checkpoint: &mut Checkpoint,
+    scraper: S,
+    report: R,
+    details: D,
+) -> Result<(usize, Vec<FailedPage>, Vec<FailedDetail>)>
+where
+    S: ListScraper,
+    R: FnOnce(Vec<S::Item>) -> Result<Vec<S::Item>>,
+    D: FnOnce(Vec<S::Item>) -> F,
+    F: Future<Output = Result<Vec<FailedDetail>>>,
+{
+    let crawled = crawl(&scraper, search, job.start_page, job.end_page, checkpoint).await?;
+    let items = report(crawled.items)?;
+    let records = items.len();
+    let failed_details = if job.details && !items.is_empty() {
+        details(items).await?
+    } else {
+        Vec::new()
+    };
+    Ok((records, crawled.failed_pages, failed_details))
+}
+
+/// 爬取列表记录的详情页并保存合并后的结果，返回失败的详情页
+async fn scrape_details<S>(
+    job: &JobConfig,
+    output_dir: &Path,
+    scraper: S,
+    items: Vec<S::Summary>,
+    write_csv: CsvWriter<DetailRecord<S::Summary, S::Detail>>,
+) -> Result<Vec<FailedDetail>>
+where
+    S: DetailScraper,
+    DetailRecord<S::Summary, S::Detail>: Serialize,
+{
+    let label = job.kind.label();
+    println!("\n=== 爬取{}详情页 ===", label);
+    let scraper = scraper
+        .with_retry_policy(job.retry.clone())
+        .with_concurrency(job.concurrency)
+        .with_base_url(job.base_url.clone());
+    let report = scraper.scrape_details(items).await?;
+
+    let data_label = format!("{}详情数据", label);
+    save_outputs(&report.records, output_dir, &job.detail_output_stem(), &job.outputs, &data_label, write_csv)?;
+    print_failed_details(&report.failed);
+    Ok(report.failed)
+}
+
+fn print_failed_details(failed: &[FailedDetail]) {
+    if !failed.is_empty() {
+        println!("\n有 {} 个详情页在重试后仍然失败:", failed.len());
+        for failed in failed {
+            println!("  {} ({} 次尝试): {}", failed.url, failed.attempts, failed.error);
+        }
+    }
+}
+
+fn report_ershoufang(job: &JobConfig, output_dir: &Path, results: Vec<ErshoufangData>) -> Result<Vec<ErshoufangData>> {
+    println!("\n在售数据爬取完成！总共获取 {} 条数据", results.len());
+
+    if results.is_empty() {
+        println!("未获取到任何在售数据，可能是页面结构发生变化或遇到反爬虫机制");
+        return Ok(results);
+    }
+
+    // 打印前几条数据作为预览
+    println!("\n=== 在售数据预览 ===");
+    for (i, data) in results.iter().take(3).enumerate() {
+        println!("\n--- 第 {} 条数据 ---", i + 1);
+        println!("编号: {}", or_unknown(data.id.as_ref(), ""));
+        println!("标题: {}", data.title);
+        println!("总价: {}", or_unknown(data.total_price, "万"));
+        println!("单价: {}", or_unknown(data.unit_price, "元/平"));
+        println!("面积: {}", or_unknown(data.area, "平米"));
+        println!("户型: {}", data.layout);
+        println!("楼层: {}", data.floor.describe());
+        println!("建成年份: {}", or_unknown(data.build_year, "年"));
+        println!("小区: {}", data.community);
+        println!("区域: {}", data.region.describe());
+        println!("标签: {}", data.tags.join(", "));
+        println!("链接: {}", data.url);
+        println!("关注人数: {}", or_unknown(data.follower_count, "人"));
+        println!("挂牌日期(约): {}", or_unknown(data.listed_on, ""));
+    }
+
+    if results.len() > 3 {
+        println!("\n... 还有 {} 条数据", results.len() - 3);
+    }
+
+    // 按任务配置的格式保存在售数据
+    save_outputs(&results, output_dir, &job.output_stem(), &job.outputs, "在售数据", save_ershoufang_to_csv)?;
+
+    // 在售数据统计信息
+    print_ershoufang_statistics(&results);
+
+    record_price_history(job, output_dir, &results)?;
+
+    Ok(results)
+}
+
+/// 把本次看到的在售价格追加到 `<name>_history.json`，并列出与上一次相比降价的房源
+fn record_price_history(job: &JobConfig, output_dir: &Path, results: &[ErshoufangData]) -> Result<()> {
+    let path = output_dir.join(format!("{}.json", job.history_output_stem()));
+    let mut history = PriceHistory::open(&path)?;
+    let observed_at = Local::now();
+    let recorded = history.record(results, observed_at);
+    history.save()?;
+
+    println!("\n=== 价格历史 ===");
+    println!("已记录 {} 套房源的价格到 {}，历史中共 {} 套房源", recorded, path.display(), history.listings.len());
+
+    let cuts: Vec<_> = history.price_cuts()
+        .into_iter()
+        .filter(|cut| cut.observed_at == observed_at)
+        .collect();
+    println!("本次发现降价: {} 套", cuts.len());
+    for cut in cuts.iter().take(10) {
+        let title = history.get(cut.listing_id.as_str()).map_or("", |listing| listing.title.as_str());
+        println!("  {} {}: {}万 -> {}万 (降价 {}万)", cut.listing_id, title, cut.previous_price, cut.price, cut.amount());
+    }
+    if cuts.len() > 10 {
+        println!("  ... 还有 {} 套", cuts.len() - 10);
+    }
+    Ok(())
+}
+
+fn print_chengjiao_statistics(data: &[ChengjiaoData]) {
+    println!("\n=== 成交数据统计 ===");
+    println!("总数据量: {} 条", data.len());
+    
+    // 装修情况统计
+    let mut renovation_stats = std::collections::HashMap::new();
+    for item in data {
+        *renovation_stats.entry(&item.is_renovated).or_insert(0) += 1;
+    }
+    
+    println!("\n装修情况分布:");
+    for (renovation, count) in renovation_stats {
+        println!("  {}: {} 条", renovation, count);
+    }
+    
+    // 价格区间统计
+    let mut price_ranges = std::collections::HashMap::new();
+    let mut missing_price = 0;
+    for item in data {
+        match item.deal_price {
+            Some(price) => *price_ranges.entry(price_range(price)).or_insert(0) += 1,
+            None => missing_price += 1,
+        }
+    }
+    
+    println!("\n成交价格分布:");
+    for (range, count) in price_ranges {
+        println!("  {}: {} 条", range, count);
+    }
+    if missing_price > 0 {
+        println!("  价格未公开: {} 条", missing_price);
+    }
+
+    // 成交周期统计
+    let cycles: Vec<u32> = data.iter().filter_map(|item| item.deal_cycle_days).collect();
+    if !cycles.is_empty() {
+        let average = cycles.iter().sum::<u32>() as f64 / cycles.len() as f64;
+        println!("\n平均成交周期: {:.1} 天 ({} 条有成交周期)", average, cycles.len());
+    }
+
+    // 让价统计
+    let discounts: Vec<f64> = data.iter().filter_map(|item| item.discount_percent).collect();
+    if !discounts.is_empty() {
+        let average = discounts.iter().sum::<f64>() / discounts.len() as f64;
+        println!("平均让价比例: {:.2}% ({} 条有挂牌价和成交价)", average, discounts.len());
+    }
+}
+
+/// 总价（万元）所在的区间
+fn price_range(price: f64) -> &'static str {
+    match price {
+        p if p < 100.0 => "100万以下",
+        p if p < 200.0 => "100-200万",
+        p if p < 300.0 => "200-300万",
+        p if p < 500.0 => "300-500万",
+        _ => "500万以上",
+    }
+}
+
+fn print_ershoufang_statistics(data: &[ErshoufangData]) {
+    println!("\n=== 在售数据统计 ===");
+    println!("总数据量: {} 条", data.len());
+    
+    // 价格区间统计
+    let mut price_ranges = std::collections::HashMap::new();
+    for price in data.iter().filter_map(|item| item.total_price) {
+        *price_ranges.entry(price_range(price)).or_insert(0) += 1;
+    }
+    
+    println!("\n在售价格分布:");
+    for (range, count) in price_ranges {
+        println!("  {}: {} 条", range, count);
+    }
+
+    // 单价统计
+    let unit_prices: Vec<f64> = data.iter().filter_map(|item| item.unit_price).collect();
+    if !unit_prices.is_empty() {
+        let average = unit_prices.iter().sum::<f64>() / unit_prices.len() as f64;
+        println!("\n平均单价: {:.0} 元/平 ({} 条有单价)", average, unit_prices.len());
+    }
+    
+    // 区域统计：按区县和商圈两级计数
+    let mut district_stats = std::collections::BTreeMap::new();
+    for item in data {
+        *district_stats.entry(item.region.district_name()).or_insert(0) += 1;
+    }
+    
+    println!("\n区域分布:");
+    for (district, count) in district_stats {
+        println!("  {}: {} 条", district, count);
+    }
+
+    let mut bizcircle_stats = std::collections::BTreeMap::new();
+    for item in data {
+        *bizcircle_stats.entry(item.region.describe()).or_insert(0) += 1;
+    }
+
+    println!("\n商圈分布:");
+    for (bizcircle, count) in bizcircle_stats {
+        println!("  {}: {} 条", bizcircle, count);
+    }
+}
+
+fn report_xiaoqu(job: &JobConfig, output_dir: &Path, results: Vec<XiaoquData>) -> Result<Vec<XiaoquData>> {
+    println!("\n小区数据爬取完成！总共获取 {} 个小区", results.len());
+
+    if results.is_empty() {
+        println!("未获取到任何小区数据，可能是页面结构发生变化或遇到反爬虫机制");
+        return Ok(results);
+    }
+
+    // 打印前几条数据作为预览
+    println!("\n=== 小区数据预览 ===");
+    for (i, data) in results.iter().take(3).enumerate() {
+        println!("\n--- 第 {} 条数据 ---", i + 1);
+        println!("小区编号: {}", or_unknown(data.id.as_ref(), ""));
+        println!("小区: {}", data.name);
+        println!("区域: {}", data.region.describe());
+        println!("参考均价: {}", or_unknown(data.average_price, "元/平"));
+        println!("建成年份: {} {}", or_unknown(data.build_year, "年"), data.building_type);
+        println!("在售: {}", or_unknown(data.on_sale_count, "套"));
+        println!("近90天成交: {}", or_unknown(data.sold_count, "套"));
+        println!("出租: {}", or_unknown(data.renting_count, "套"));
+    }
+
+    if results.len() > 3 {
+        println!("\n... 还有 {} 个小区", results.len() - 3);
+    }
+
+    // 按任务配置的格式保存小区数据
+    save_outputs(&results, output_dir, &job.output_stem(), &job.outputs, "小区数据", save_xiaoqu_to_csv)?;
+
+    print_xiaoqu_statistics(&results);
+
+    Ok(results)
+}
+
+fn print_xiaoqu_statistics(data: &[XiaoquData]) {
+    println!("\n=== 小区数据统计 ===");
+    println!("小区数量: {} 个", data.len());
+
+    let prices: Vec<f64> = data.iter().filter_map(|item| item.average_price).collect();
+    if !prices.is_empty() {
+        let average = prices.iter().sum::<f64>() / prices.len() as f64;
+        println!("\n小区均价的平均值: {:.0} 元/平 ({} 个小区有均价)", average, prices.len());
+    }
+
+    let on_sale: u32 = data.iter().filter_map(|item| item.on_sale_count).sum();
+    let sold: u32 = data.iter().filter_map(|item| item.sold_count).sum();
+    println!("在售合计: {} 套", on_sale);
+    println!("近90天成交合计: {} 套", sold);
+}
+
+fn report_zufang(job: &JobConfig, output_dir: &Path, results: Vec<ZufangData>) -> Result<Vec<ZufangData>> {
+    println!("\n租房数据爬取完成！总共获取 {} 条数据", results.len());
+
+    if results.is_empty() {
+        println!("未获取到任何租房数据，可能是页面结构发生变化或遇到反爬虫机制");
+        return Ok(results);
+    }
+
+    // 打印前几条数据作为预览
+    println!("\n=== 租房数据预览 ===");
+    for (i, data) in results.iter().take(3).enumerate() {
+        println!("\n--- 第 {} 条数据 ---", i + 1);
+        println!("编号: {}", or_unknown(data.id.as_ref(), ""));
+        println!("标题: {}", data.title);
+        println!("出租方式: {}", or_unknown(data.rental_type.map(|t| t.label()), ""));
+        println!("月租金: {}", or_unknown(data.rent, "元/月"));
+        println!("面积: {}", or_unknown(data.area, "平米"));
+        println!("户型: {}", data.layout);
+        println!("朝向: {}", data.orientation);
+        println!("楼层: {}", data.floor.describe());
+        println!("小区: {}", data.community);
+        println!("区域: {}", data.region.describe());
+        println!("维护日期(约): {}", or_unknown(data.listed_on, ""));
+    }
+
+    if results.len() > 3 {
+        println!("\n... 还有 {} 条数据", results.len() - 3);
+    }
+
+    // 按任务配置的格式保存租房数据
+    save_outputs(&results, output_dir, &job.output_stem(), &job.outputs, "租房数据", save_zufang_to_csv)?;
+
+    print_zufang_statistics(&results);
+
+    Ok(results)
+}
+
+fn print_zufang_statistics(data: &[ZufangData]) {
+    println!("\n=== 租房数据统计 ===");
+    println!("总数据量: {} 条", data.len());
+
+    let mut type_stats = std::collections::BTreeMap::new();
+    for item in data {
+        *type_stats.entry(item.rental_type.map_or("未知", |t| t.label())).or_insert(0) += 1;
+    }
+
+    println!("\n出租方式分布:");
+    for (rental_type, count) in type_stats {
+        println!("  {}: {} 条", rental_type, count);
+    }
+
+    let rents: Vec<f64> = data.iter().filter_map(|item| item.rent).collect();
+    if !rents.is_empty() {
+        let average = rents.iter().sum::<f64>() / rents.len() as f64;
+        println!("\n平均月租金: {:.0} 元 ({} 条有租金)", average, rents.len());
+    }
+
+    // 合租的面积是单间面积，每平米租金只按整租计算
+    let unit_rents: Vec<f64> = data.iter()
+        .filter(|item| item.rental_type == Some(RentalType::Whole))
+        .filter_map(|item| item.rent_per_square_meter())
+        .collect();
+    if !unit_rents.is_empty() {
+        let average = unit_rents.iter().sum::<f64>() / unit_rents.len() as f64;
+        println!("整租平均每平米月租金: {:.2} 元 ({} 条有租金和面积)", average, unit_rents.len());
+    }
+
+    let mut bizcircle_stats = std::collections::BTreeMap::new();
+    for item in data {
+        *bizcircle_stats.entry(item.region.describe()).or_insert(0) += 1;
+    }
+
+    println!("\n商圈分布:");
+    for (bizcircle, count) in bizcircle_stats {
+        println!("  {}: {} 条", bizcircle, count);
+    }
+}
+
+fn report_loupan(job: &JobConfig, output_dir: &Path, results: Vec<LoupanData>) -> Result<Vec<LoupanData>> {
+    println!("\n新房数据爬取完成！总共获取 {} 个楼盘", results.len());
+
+    if results.is_empty() {
+        println!("未获取到任何新房数据，可能是页面结构发生变化或遇到反爬虫机制");
+        return Ok(results);
+    }
+
+    // 打印前几条数据作为预览
+    println!("\n=== 新房数据预览 ===");
+    for (i, data) in results.iter().take(3).enumerate() {
+        println!("\n--- 第 {} 条数据 ---", i + 1);
+        println!("楼盘编号: {}", or_unknown(data.id.as_ref(), ""));
+        println!("楼盘: {} ({})", data.name, data.property_type);
+        println!("销售状态: {}", or_unknown(data.sale_status.map(|s| s.label()), ""));
+        println!("区域: {} {}", data.region.describe(), data.address);
+        println!("均价: {}", or_unknown(data.average_price, "元/平"));
+        println!(
+            "总价: {} - {}",
+            or_unknown(data.total_price_min, "万"),
+            or_unknown(data.total_price_max, "万")
+        );
+        println!("户型: {}", data.unit_types.join(", "));
+        println!("面积: {} - {}", or_unknown(data.area_min, "平米"), or_unknown(data.area_max, "平米"));
+    }
+
+    if results.len() > 3 {
+        println!("\n... 还有 {} 个楼盘", results.len() - 3);
+    }
+
+    // 按任务配置的格式保存新房数据
+    save_outputs(&results, output_dir, &job.output_stem(), &job.outputs, "新房数据", save_loupan_to_csv)?;
+
+    print_loupan_statistics(&results);
+
+    Ok(results)
+}
+
+fn print_loupan_statistics(data: &[LoupanData]) {
+    println!("\n=== 新房数据统计 ===");
+    println!("楼盘数量: {} 个", data.len());
+
+    let mut status_stats = std::collections::BTreeMap::new();
+    for item in data {
+        *status_stats.entry(item.sale_status.map_or("未知", |s| s.label())).or_insert(0) += 1;
+    }
+
+    println!("\n销售状态分布:");
+    for (status, count) in status_stats {
+        println!("  {}: {} 个", status, count);
+    }
+
+    // 最低总价所在的区间，与在售、成交的总价区间一致，便于对比
+    let mut price_ranges = std::collections::HashMap::new();
+    for price in data.iter().filter_map(|item| item.total_price_min) {
+        *price_ranges.entry(price_range(price)).or_insert(0) += 1;
+    }
+
+    println!("\n最低总价分布:");
+    for (range, count) in price_ranges {
+        println!("  {}: {} 个", range, count);
+    }
+
+    let prices: Vec<f64> = data.iter().filter_map(|item| item.average_price).collect();
+    if !prices.is_empty() {
+        let average = prices.iter().sum::<f64>() / prices.len() as f64;
+        println!("\n楼盘均价的平均值: {:.0} 元/平 ({} 个楼盘有均价)", average, prices.len());
+    }
+
+    let mut bizcircle_stats = std::collections::BTreeMap::new();
+    for item in data {
+        *bizcircle_stats.entry(item.region.describe()).or_insert(0) += 1;
+    }
+
+    println!("\n商圈分布:");
+    for (bizcircle, count) in bizcircle_stats {
+        println!("  {}: {} 个", bizcircle, count);
+    }
+}
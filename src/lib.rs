@@ -0,0 +1,36 @@
+//! 贝壳网成交/在售数据爬虫
+//!
+//! 命令行入口在 `main.rs`，这里导出各模块以便集成测试直接调用爬虫和本地模拟服务器。
+
+pub mod archive;
+pub mod checkpoint;
+pub mod chengjiao;
+pub mod chengjiao_detail;
+pub mod cli;
+pub mod community;
+pub mod config;
+pub mod crawl;
+pub mod detail;
+pub mod error;
+pub mod ershoufang;
+pub mod ershoufang_detail;
+pub mod fetch;
+pub mod filter;
+pub mod house;
+pub mod job;
+pub mod listing;
+pub mod loupan;
+pub mod loupan_detail;
+pub mod mock;
+pub mod output;
+pub mod pagination;
+pub mod price_history;
+pub mod ratelimit;
+pub mod region;
+pub mod reparse;
+pub mod retry;
+pub mod search_url;
+pub mod text;
+pub mod xiaoqu;
+pub mod xiaoqu_detail;
+pub mod zufang;
@@ -0,0 +1,55 @@
+use scraper::ElementRef;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// 列表项上携带房源编号的 data 属性，链接中没有编号时使用
+const ID_ATTRIBUTES: [&str; 4] = ["data-housecode", "data-house_code", "data-lj_action_housedel_id", "data-id"];
+
+/// 房源的稳定编号：在售房源的 housecode、成交记录或租房房源的编号
+///
+/// 就是详情链接中的编号（`/ershoufang/106118000001.html`、`/chengjiao/106117000001.html`、
+/// 租房的 `/zufang/CQ1234567890.html`），
+/// 同一套房源在列表页、详情页和不同批次的爬取中保持不变，可作为去重和关联的主键。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ListingId(String);
+
+impl ListingId {
+    /// 只接受纯数字或 "大写城市缩写+数字" 形式的编号，如 106118000001、CQ1234567890
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let digits = text.trim_start_matches(|c: char| c.is_ascii_uppercase());
+        (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())).then(|| Self(text.to_string()))
+    }
+
+    /// 从详情链接中取出编号，如 `https://cq.ke.com/ershoufang/106118000001.html` -> 106118000001
+    pub fn from_url(url: &str) -> Option<Self> {
+        let path = url.split(['?', '#']).next()?;
+        let file = path.trim_end_matches('/').rsplit('/').next()?;
+        Self::parse(file.strip_suffix(".html")?)
+    }
+
+    /// 从列表项中取出编号：先看标题等链接，再看 data 属性
+    pub fn from_element(item: &ElementRef) -> Option<Self> {
+        let elements = || std::iter::once(*item).chain(item.descendants().filter_map(ElementRef::wrap));
+
+        elements()
+            .filter_map(|el| el.value().attr("href"))
+            .find_map(Self::from_url)
+            .or_else(|| {
+                elements().find_map(|el| {
+                    ID_ATTRIBUTES.iter().find_map(|attr| el.value().attr(attr).and_then(Self::parse))
+                })
+            })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ListingId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
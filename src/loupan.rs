@@ -0,0 +1,311 @@
+use anyhow::Result;
+use scraper::{ElementRef, Html, Selector};
+use crate::checkpoint::Checkpoint;
+use crate::crawl::{crawl, CrawlReport, ListPage, ListScraper};
+use crate::error::ScrapeError;
+use crate::fetch::Fetcher;
+use crate::pagination::PageInfo;
+use crate::region::{city_of, Area, Region, RegionFilter};
+use crate::retry::RetryPolicy;
+use crate::search_url::SearchUrl;
+use crate::text::parse_number;
+use serde::{Deserialize, Serialize};
+
+/// 楼盘的销售状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SaleStatus {
+    #[serde(rename = "在售")]
+    OnSale,
+    #[serde(rename = "待售")]
+    Upcoming,
+    #[serde(rename = "售罄")]
+    SoldOut,
+}
+
+impl SaleStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SaleStatus::OnSale => "在售",
+            SaleStatus::Upcoming => "待售",
+            SaleStatus::SoldOut => "售罄",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match text.trim() {
+            "在售" => Some(SaleStatus::OnSale),
+            "待售" | "即将开盘" => Some(SaleStatus::Upcoming),
+            "售罄" => Some(SaleStatus::SoldOut),
+            _ => None,
+        }
+    }
+}
+
+/// 新房列表页上的一个楼盘
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoupanData {
+    /// 楼盘编号，即 /loupan/{id}/ 中的 p_ 开头的标识
+    pub id: Option<String>,
+    pub name: String,
+    /// 楼盘主页链接，列表页上是相对地址，爬取时按列表页地址补全
+    pub url: String,
+    /// 物业类型，如 "住宅"、"别墅"、"商业"
+    pub property_type: String,
+    /// 页面上没有或无法识别的状态为 None，原始文本见 `raw.sale_status`
+    pub sale_status: Option<SaleStatus>,
+    /// 所在城市、区县和商圈，区县和商圈名称在区域筛选栏的层级中查找
+    pub region: Region,
+    /// 楼盘地址，如 "金山大道与星光大道交汇处"
+    pub address: String,
+    /// 均价（元/平米），只标了总价的楼盘为 None
+    pub average_price: Option<f64>,
+    /// 总价区间（万元/套）
+    pub total_price_min: Option<f64>,
+    pub total_price_max: Option<f64>,
+    /// 在售户型，如 ["3室", "4室"]
+    pub unit_types: Vec<String>,
+    /// 建筑面积区间（平方米）
+    pub area_min: Option<f64>,
+    pub area_max: Option<f64>,
+    pub tags: Vec<String>,
+    /// 页面上的原始文本，用于核对解析结果
+    pub raw: LoupanRaw,
+}
+
+/// 楼盘各字段在页面上的原始文本，没有找到时为空
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoupanRaw {
+    pub sale_status: String,
+    /// 主价格，如 "15000 元/㎡(均价)" 或 "135 万/套(总价)"
+    pub main_price: String,
+    /// 第二行价格，如 "总价135-210(万/套)"
+    pub second_price: String,
+    /// 面积，如 "建面 89-143㎡"
+    pub area: String,
+    /// 位置，如 "渝北 / 大竹林 / 金山大道与星光大道交汇处"
+    pub location: String,
+}
+
+pub struct LoupanScraper {
+    fetcher: Fetcher,
+    retry_policy: RetryPolicy,
+    concurrency: usize,
+}
+
+impl LoupanScraper {
+    pub fn new(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher,
+            retry_policy: RetryPolicy::default(),
+            concurrency: 1,
+        }
+    }
+
+    /// 设置单页失败时的重试策略
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// 设置同时在途的列表页请求数，实际请求速率仍受 Fetcher 的限速约束
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub async fn scrape_loupan_page(&self, url: &str) -> Result<ListPage<LoupanData>, ScrapeError> {
+        let html_content = self.fetcher.fetch(url).await?;
+        self.parse_page_at(&html_content, url).non_empty()
+    }
+
+    /// 解析新房列表页 HTML，不发起任何网络请求，链接保持页面上的相对地址
+    pub fn parse_page(&self, html_content: &str) -> ListPage<LoupanData> {
+        self.parse(html_content, None)
+    }
+
+    /// 解析从 `page_url` 抓取的列表页 HTML，相对链接按 `page_url` 补全
+    pub fn parse_page_at(&self, html_content: &str, page_url: &str) -> ListPage<LoupanData> {
+        self.parse(html_content, reqwest::Url::parse(page_url).ok())
+    }
+
+    fn parse(&self, html_content: &str, page_url: Option<reqwest::Url>) -> ListPage<LoupanData> {
+        let document = Html::parse_document(html_content);
+
+        let list_selector = Selector::parse(".resblock-list-wrapper li.resblock-list").unwrap();
+        let regions = RegionFilter::parse(&document, "loupan");
+
+        let mut results: Vec<LoupanData> = Vec::new();
+        for item in document.select(&list_selector) {
+            match self.parse_list_item(&item, page_url.as_ref(), &regions) {
+                Ok(loupan_data) => results.push(loupan_data),
+                Err(e) => println!("跳过列表项: {}", e),
+            }
+        }
+
+        println!("成功解析 {} 条新房数据", results.len());
+        ListPage { items: results, info: PageInfo::parse(&document) }
+    }
+
+    fn parse_list_item(&self, item: &ElementRef, page_url: Option<&reqwest::Url>, regions: &RegionFilter) -> Result<LoupanData, ScrapeError> {
+        let name_selector = Selector::parse(".resblock-name a.name").unwrap();
+        let name_link = item.select(&name_selector).next().ok_or_else(|| ScrapeError::Parse {
+            field: "name",
+            reason: "列表项中没有 .resblock-name a.name".to_string(),
+        })?;
+        let name = name_link.text().collect::<String>().trim().to_string();
+        let href = name_link.value().attr("href").unwrap_or("");
+        let url = match page_url.and_then(|base| base.join(href).ok()) {
+            Some(url) if !href.is_empty() => url.to_string(),
+            _ => href.to_string(),
+        };
+
+        let raw = LoupanRaw {
+            sale_status: select_text(item, ".resblock-name .sale-status"),
+            main_price: select_text(item, ".resblock-price .main-price"),
+            second_price: select_text(item, ".resblock-price .second"),
+            area: select_text(item, ".resblock-room .area"),
+            location: select_text(item, ".resblock-location"),
+        };
+
+        // 位置：<span>渝北</span><i>/</i><span>大竹林</span><i>/</i><a>金山大道与星光大道交汇处</a>
+        let span_selector = Selector::parse(".resblock-location span").unwrap();
+        let names: Vec<String> = item.select(&span_selector)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .collect();
+        let address_selector = Selector::parse(".resblock-location a").unwrap();
+        let address = item.select(&address_selector)
+            .next()
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
+        let region = locate_by_name(
+            regions,
+            names.first().map(String::as_str),
+            names.get(1).map(String::as_str),
+            city_of(&url),
+        );
+
+        // 主价格是均价或总价，第二行通常是总价区间
+        let (average_price, mut total_price) = if raw.main_price.contains("总价") || raw.main_price.contains("万/套") {
+            (None, parse_range(&raw.main_price))
+        } else {
+            (parse_number(&raw.main_price), (None, None))
+        };
+        if raw.second_price.contains("总价") {
+            total_price = parse_range(&raw.second_price);
+        }
+        let (area_min, area_max) = parse_range(&raw.area);
+
+        let room_selector = Selector::parse(".resblock-room span:not(.area)").unwrap();
+        let unit_types: Vec<String> = item.select(&room_selector)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|room| !room.is_empty())
+            .collect();
+
+        let tag_selector = Selector::parse(".resblock-tag span").unwrap();
+        let tags: Vec<String> = item.select(&tag_selector)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+
+        Ok(LoupanData {
+            id: project_id(&url),
+            name,
+            url,
+            property_type: Some(select_text(item, ".resblock-name .resblock-type"))
+                .filter(|text| !text.is_empty())
+                .unwrap_or_else(|| "未知".to_string()),
+            sale_status: SaleStatus::parse(&raw.sale_status),
+            region,
+            address,
+            average_price,
+            total_price_min: total_price.0,
+            total_price_max: total_price.1,
+            unit_types,
+            area_min,
+            area_max,
+            tags,
+            raw,
+        })
+    }
+
+    /// 爬取 `start_page` 到 `end_page` 的新房列表页，`end_page` 为 None 时根据第一页的分页信息自动确定页数
+    ///
+    /// 每页结果写入 `checkpoint`，已在断点中完成的页会被跳过。
+    pub async fn scrape_multiple_pages(&self, search: &SearchUrl, start_page: u32, end_page: Option<u32>, checkpoint: &mut Checkpoint) -> Result<CrawlReport<LoupanData>> {
+        crawl(self, search, start_page, end_page, checkpoint).await
+    }
+}
+
+impl ListScraper for LoupanScraper {
+    type Item = LoupanData;
+
+    async fn scrape_page(&self, url: &str) -> Result<ListPage<LoupanData>, ScrapeError> {
+        self.scrape_loupan_page(url).await
+    }
+
+    fn item_key(item: &LoupanData) -> String {
+        match &item.id {
+            Some(id) => id.clone(),
+            None if !item.url.is_empty() => item.url.clone(),
+            None => item.name.clone(),
+        }
+    }
+
+    fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+}
+
+/// 楼盘链接中的编号，如 `https://cq.fang.ke.com/loupan/p_jktlcbkcvf/` -> p_jktlcbkcvf
+pub fn project_id(href: &str) -> Option<String> {
+    let path = href.split(['?', '#']).next()?;
+    let mut parts = path.split('/').filter(|part| !part.is_empty());
+    parts.find(|part| *part == "loupan")?;
+    parts
+        .next()
+        .filter(|id| id.len() > 2 && id.starts_with("p_"))
+        .map(str::to_string)
+}
+
+/// 列表项上的区县、商圈只有名称没有链接，在筛选栏的层级中按名称查找拼音标识
+///
+/// 层级中没有的区域（如没有选中区县时的商圈）只保留名称，拼音标识为空。
+fn locate_by_name(regions: &RegionFilter, district: Option<&str>, bizcircle: Option<&str>, city: Option<String>) -> Region {
+    let nodes = &regions.tree.districts;
+    let bizcircle = bizcircle.map(|name| find_area(nodes.iter().flat_map(|node| &node.bizcircles), name));
+    let mut region = regions.locate(bizcircle, city);
+    if let Some(name) = district {
+        region.district = Some(find_area(nodes.iter().map(|node| &node.district), name));
+    }
+    region
+}
+
+fn find_area<'a>(mut areas: impl Iterator<Item = &'a Area>, name: &str) -> Area {
+    areas
+        .find(|area| area.name == name)
+        .cloned()
+        .unwrap_or_else(|| Area { slug: String::new(), name: name.to_string() })
+}
+
+/// "建面 89-143㎡" -> (89.0, 143.0)，"总价135(万/套)" -> (135.0, 135.0)
+fn parse_range(text: &str) -> (Option<f64>, Option<f64>) {
+    match text.split_once(['-', '~']) {
+        Some((min, max)) => (parse_number(min), parse_number(max)),
+        None => {
+            let value = parse_number(text);
+            (value, value)
+        }
+    }
+}
+
+fn select_text(item: &ElementRef, selector: &str) -> String {
+    let selector = Selector::parse(selector).unwrap();
+    item.select(&selector)
+        .next()
+        .map(|el| el.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default()
+}
@@ -0,0 +1,121 @@
+use crate::detail::{attributes, canonical_url, check_id, known, DetailOptions, DetailRecord, DetailScraper};
+use crate::error::ScrapeError;
+use crate::fetch::Fetcher;
+use crate::loupan::{project_id, LoupanData};
+use crate::text::{parse_date, parse_integer, parse_number};
+use anyhow::Result;
+use chrono::NaiveDate;
+use scraper::Html;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 楼盘详情页（`/loupan/{id}/xiangqing/`）上的信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoupanDetail {
+    /// 楼盘编号，取自页面的规范地址 `/loupan/{id}/xiangqing/`
+    pub project_id: Option<String>,
+    pub developer: Option<String>,
+    /// 最新开盘日期，页面上只写到月份时为 None，原文见 `attributes["最新开盘"]`
+    pub opening_date: Option<NaiveDate>,
+    /// 交房日期，规则同上
+    pub delivery_date: Option<NaiveDate>,
+    /// 产权年限（年）
+    pub property_right_years: Option<u32>,
+    /// 规划户数
+    pub planned_households: Option<u32>,
+    pub property_company: Option<String>,
+    /// 物业费（元/平米/月），显示区间时取下限
+    pub property_fee: Option<f64>,
+    /// 容积率
+    pub plot_ratio: Option<f64>,
+    /// 绿化率（%）
+    pub greening_rate: Option<f64>,
+    /// 楼盘详情中的全部条目，名称去掉了末尾的冒号
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// 新房列表记录与详情合并后的记录
+pub type LoupanRecord = DetailRecord<LoupanData, LoupanDetail>;
+
+pub struct LoupanDetailScraper {
+    fetcher: Fetcher,
+    options: DetailOptions,
+}
+
+impl LoupanDetailScraper {
+    pub fn new(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher,
+            options: DetailOptions::default(),
+        }
+    }
+
+    pub async fn scrape_loupan_detail(&self, url: &str) -> Result<LoupanDetail, ScrapeError> {
+        let html_content = self.fetcher.fetch(url).await?;
+        let detail = self.parse_detail(&html_content);
+        if detail.attributes.is_empty() {
+            return Err(ScrapeError::EmptyPage);
+        }
+        Ok(detail)
+    }
+
+    /// 解析详情页 HTML，不发起任何网络请求
+    pub fn parse_detail(&self, html_content: &str) -> LoupanDetail {
+        let document = Html::parse_document(html_content);
+
+        // 楼盘详情：<li><span class="label">开发商：</span><span class="label-val">重庆金科房地产开发有限公司</span></li>
+        let attributes: BTreeMap<String, String> = attributes(&document, ".x-box li")
+            .into_iter()
+            .map(|(name, value)| (name.trim_end_matches(['：', ':']).trim().to_string(), value))
+            .filter(|(name, _)| !name.is_empty())
+            .collect();
+        let attribute = |name: &str| known(attributes.get(name));
+
+        LoupanDetail {
+            project_id: canonical_url(&document).and_then(|url| project_id(&url)),
+            developer: attribute("开发商"),
+            opening_date: attribute("最新开盘").and_then(|text| parse_date(&text)),
+            delivery_date: attribute("交房时间").and_then(|text| parse_date(&text)),
+            property_right_years: attribute("产权年限").and_then(|text| parse_integer(&text)),
+            planned_households: attribute("规划户数").and_then(|text| parse_integer(&text)),
+            property_company: attribute("物业公司"),
+            property_fee: attribute("物业费").and_then(|text| parse_number(&text)),
+            plot_ratio: attribute("容积率").and_then(|text| parse_number(&text)),
+            greening_rate: attribute("绿化率").and_then(|text| parse_number(&text)),
+            attributes,
+        }
+    }
+}
+
+impl DetailScraper for LoupanDetailScraper {
+    type Summary = LoupanData;
+    type Detail = LoupanDetail;
+
+    fn options(&self) -> &DetailOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut DetailOptions {
+        &mut self.options
+    }
+
+    fn summary_url(summary: &LoupanData) -> &str {
+        &summary.url
+    }
+
+    /// 楼盘主页 `/loupan/{id}/` 下的 `xiangqing/` 页
+    fn detail_url(&self, summary: &LoupanData) -> Option<String> {
+        let url = self.options.rebase(&summary.url)?;
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        Some(format!("{}/xiangqing/", path.trim_end_matches('/')))
+    }
+
+    async fn scrape_detail(&self, url: &str) -> Result<LoupanDetail, ScrapeError> {
+        self.scrape_loupan_detail(url).await
+    }
+
+    /// 详情页上的楼盘编号与列表记录不一致时（如被重定向到了其他楼盘）不合并
+    fn check_detail(&self, summary: &LoupanData, detail: &LoupanDetail) -> Result<(), ScrapeError> {
+        check_id("project_id", "楼盘编号", summary.id.as_ref(), detail.project_id.as_ref())
+    }
+}
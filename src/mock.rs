@@ -0,0 +1,177 @@
+use crate::archive::Archive;
+use anyhow::Result;
+use reqwest::{StatusCode, Url};
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// 模拟服务器对某个路径的一次响应
+#[derive(Debug, Clone)]
+pub enum MockResponse {
+    /// 200，返回给定的 HTML
+    Html(String),
+    /// 200，但内容是人机验证页面
+    CaptchaPage,
+    /// 302 跳转到验证页
+    CaptchaRedirect,
+    /// 302 跳转到登录页，模拟 Cookie 过期
+    LoginRedirect,
+    /// 普通跳转，如补全结尾 `/` 的 301
+    Redirect { status: u16, location: String },
+    /// 指定状态码，响应体为空，如 500、503、429
+    Status(u16),
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    /// 每个路径按顺序返回的响应，只剩最后一个时重复返回它
+    routes: HashMap<String, VecDeque<MockResponse>>,
+    /// 收到的请求路径，按到达顺序记录
+    requests: Vec<String>,
+}
+
+/// 本地模拟的贝壳网服务器，用于离线测试爬虫
+///
+/// 每个路径可以配置一串响应（如先返回 503 再返回正常页面），未配置的路径返回 404。
+/// 配合 [`crate::search_url::SearchUrl::base_url`] 把爬虫指向 [`MockServer::base_url`] 即可。
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// 在 127.0.0.1 的随机端口上启动
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let server_state = state.clone();
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, state).await {
+                        println!("模拟服务器处理请求失败: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { addr, state, handle })
+    }
+
+    /// 形如 `http://127.0.0.1:12345`，不带结尾的 `/`
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// `path` 始终返回 `response`
+    pub fn route(&self, path: &str, response: MockResponse) {
+        self.route_sequence(path, vec![response]);
+    }
+
+    /// `path` 依次返回 `responses` 中的响应，最后一个会一直重复
+    pub fn route_sequence(&self, path: &str, responses: Vec<MockResponse>) {
+        let mut state = self.state.lock().unwrap();
+        state.routes.insert(path.to_string(), responses.into());
+    }
+
+    /// 把归档中的页面按原来的路径提供出来，重放一次真实的爬取
+    pub fn serve_archive(&self, archive: &Archive) -> Result<usize> {
+        let pages = archive.load_all()?;
+        for page in &pages {
+            let url = Url::parse(&page.url)?;
+            self.route(url.path(), MockResponse::Html(page.body.clone()));
+        }
+        Ok(pages.len())
+    }
+
+    /// 已收到的请求路径
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// `path` 被请求的次数
+    pub fn request_count(&self, path: &str) -> usize {
+        self.state.lock().unwrap().requests.iter().filter(|p| *p == path).count()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<MockState>>) -> Result<()> {
+    // 只需要请求行，读到头部结束即可，爬虫只发 GET 请求
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while !buffer.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+
+    let request = String::from_utf8_lossy(&buffer);
+    let path = request
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or("/")
+        .to_string();
+
+    let response = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(path.clone());
+        let key = if state.routes.contains_key(&path) {
+            path.clone()
+        } else {
+            path.split('?').next().unwrap_or(&path).to_string()
+        };
+        match state.routes.get_mut(&key) {
+            Some(queue) if queue.len() > 1 => queue.pop_front(),
+            Some(queue) => queue.front().cloned(),
+            None => None,
+        }
+    };
+
+    let (status, location, body) = match response {
+        Some(MockResponse::Html(html)) => (200, None, html),
+        Some(MockResponse::CaptchaPage) => (200, None, CAPTCHA_PAGE.to_string()),
+        Some(MockResponse::CaptchaRedirect) => (302, Some(format!("/captcha?redirect={}", path)), String::new()),
+        Some(MockResponse::LoginRedirect) => (302, Some(format!("/login?redirect={}", path)), String::new()),
+        Some(MockResponse::Redirect { status, location }) => (status, Some(location), String::new()),
+        Some(MockResponse::Status(code)) => (code, None, String::new()),
+        None => (404, None, String::new()),
+    };
+
+    let reason = StatusCode::from_u16(status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("");
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        reason,
+        body.len()
+    );
+    if let Some(location) = location {
+        head.push_str(&format!("Location: {}\r\n", location));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+const CAPTCHA_PAGE: &str = "<html><head><title>人机验证</title></head><body><div class=\"captcha\">请完成人机验证后继续访问</div></body></html>";
@@ -0,0 +1,403 @@
+use crate::chengjiao::ChengjiaoData;
+use crate::chengjiao_detail::ChengjiaoRecord;
+use crate::ershoufang::ErshoufangData;
+use crate::community::Community;
+use crate::ershoufang_detail::ErshoufangRecord;
+use crate::loupan::LoupanData;
+use crate::loupan_detail::LoupanRecord;
+use crate::price_history::PriceChangeSummary;
+use crate::xiaoqu::XiaoquData;
+use crate::xiaoqu_detail::XiaoquRecord;
+use crate::zufang::ZufangData;
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Json,
+    Csv,
+}
+
+pub fn default_formats() -> Vec<OutputFormat> {
+    vec![OutputFormat::Json, OutputFormat::Csv]
+}
+
+/// 把一组记录写成 CSV 文件的函数，如 [`save_chengjiao_to_csv`]
+pub type CsvWriter<T> = fn(&[T], &Path) -> Result<()>;
+
+/// 按指定格式保存数据，文件名为 `<stem>.json` / `<stem>.csv`，`label` 是日志中的数据名称，如 "成交数据"
+pub fn save_outputs<T: Serialize>(
+    data: &[T],
+    output_dir: &Path,
+    stem: &str,
+    formats: &[OutputFormat],
+    label: &str,
+    write_csv: CsvWriter<T>,
+) -> Result<()> {
+    for format in formats {
+        match format {
+            OutputFormat::Json => {
+                let path = output_dir.join(format!("{}.json", stem));
+                fs::write(&path, serde_json::to_string_pretty(data)?)?;
+                println!("\n{}已保存到 {}", label, path.display());
+            }
+            OutputFormat::Csv => {
+                let path = output_dir.join(format!("{}.csv", stem));
+                write_csv(data, &path)?;
+                println!("{}已保存到 {}", label, path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn save_chengjiao_to_csv(data: &[ChengjiaoData], filename: &Path) -> Result<()> {
+    let mut csv_content = String::new();
+    csv_content.push_str("编号,标题,小区,小区编号,成交价(万),挂牌价(万),让价(万),让价比例(%),单价(元/平),成交日期,装修情况,成交周期(天),朝向,楼层位置,总层数,建成年份,建筑类型\n");
+    
+    for item in data {
+        csv_content.push_str(&format!(
+            "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
+            csv_value(item.id.as_ref()),
+            item.title,
+            item.community,
+            csv_value(item.community_id.as_deref()),
+            csv_value(item.deal_price),
+            csv_value(item.list_price),
+            csv_value(item.discount),
+            csv_value(item.discount_percent),
+            csv_value(item.unit_price),
+            csv_value(item.deal_date),
+            item.is_renovated,
+            csv_value(item.deal_cycle_days),
+            item.orientation,
+            csv_value(item.floor.position.map(|p| p.label())),
+            csv_value(item.floor.total_floors),
+            csv_value(item.build_year),
+            item.building_type
+        ));
+    }
+    
+    fs::write(filename, csv_content)?;
+    Ok(())
+}
+
+/// 只包含详情页上的主要字段，完整的属性和历史成交记录见 JSON
+pub fn save_chengjiao_detail_to_csv(data: &[ChengjiaoRecord], filename: &Path) -> Result<()> {
+    let mut csv_content = String::new();
+    csv_content.push_str("编号,标题,成交价(万),挂牌价(万),成交日期,挂牌时间,调价(次),带看(次),关注(人),浏览(次),交易权属,产权年限,供暖方式,梯户比例,配备电梯,历史成交(次),链接\n");
+
+    for record in data {
+        let item = &record.summary;
+        let detail = record.detail.as_ref();
+        csv_content.push_str(&format!(
+            "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
+            csv_value(item.id.as_ref().or(detail.and_then(|d| d.deal_id.as_ref()))),
+            item.title,
+            csv_value(item.deal_price),
+            csv_value(item.list_price),
+            csv_value(item.deal_date),
+            csv_value(detail.and_then(|d| d.listed_on)),
+            csv_value(detail.and_then(|d| d.price_adjustments)),
+            csv_value(detail.and_then(|d| d.showings)),
+            csv_value(detail.and_then(|d| d.followers)),
+            csv_value(detail.and_then(|d| d.views)),
+            csv_value(detail.and_then(|d| d.ownership.as_deref())),
+            csv_value(detail.and_then(|d| d.property_right_years)),
+            csv_value(detail.and_then(|d| d.heating.as_deref())),
+            csv_value(detail.and_then(|d| d.elevator_ratio.as_deref())),
+            csv_value(detail.and_then(|d| d.has_elevator).map(|e| if e { "有" } else { "无" })),
+            csv_value(detail.map(|d| d.history.len())),
+            item.url
+        ));
+    }
+
+    fs::write(filename, csv_content)?;
+    Ok(())
+}
+
+/// 只包含详情页上的主要字段，完整的属性见 JSON
+pub fn save_ershoufang_detail_to_csv(data: &[ErshoufangRecord], filename: &Path) -> Result<()> {
+    let mut csv_content = String::new();
+    csv_content.push_str("编号,标题,总价(万),小区编号,经度,纬度,经纪人,经纪人电话,朝向,户型结构,建筑结构,梯户比例,配备电梯,产权年限,交易权属,挂牌时间,上次交易,房屋年限,抵押,链接\n");
+
+    for record in data {
+        let item = &record.summary;
+        let detail = record.detail.as_ref();
+        let agent = detail.and_then(|d| d.agent.as_ref());
+        csv_content.push_str(&format!(
+            "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
+            csv_value(item.id.as_ref().or(detail.and_then(|d| d.listing_id.as_ref()))),
+            item.title,
+            csv_value(item.total_price),
+            csv_value(detail.and_then(|d| d.community_id.as_deref())),
+            csv_value(detail.and_then(|d| d.coordinates).map(|c| c.longitude)),
+            csv_value(detail.and_then(|d| d.coordinates).map(|c| c.latitude)),
+            csv_value(agent.map(|a| a.name.as_str())),
+            csv_value(agent.and_then(|a| a.phone.as_deref())),
+            csv_value(detail.and_then(|d| d.orientation.as_deref())),
+            csv_value(detail.and_then(|d| d.structure.as_deref())),
+            csv_value(detail.and_then(|d| d.building_structure.as_deref())),
+            csv_value(detail.and_then(|d| d.elevator_ratio.as_deref())),
+            csv_value(detail.and_then(|d| d.has_elevator).map(|e| if e { "有" } else { "无" })),
+            csv_value(detail.and_then(|d| d.property_right_years)),
+            csv_value(detail.and_then(|d| d.ownership.as_deref())),
+            csv_value(detail.and_then(|d| d.listed_on)),
+            csv_value(detail.and_then(|d| d.last_transaction)),
+            csv_value(detail.and_then(|d| d.holding_period.as_deref())),
+            csv_value(detail.and_then(|d| d.mortgaged).map(|m| if m { "有抵押" } else { "无抵押" })),
+            item.url
+        ));
+    }
+
+    fs::write(filename, csv_content)?;
+    Ok(())
+}
+
+pub fn save_ershoufang_to_csv(data: &[ErshoufangData], filename: &Path) -> Result<()> {
+    let mut csv_content = String::new();
+    csv_content.push_str("编号,标题,总价(万),单价(元/平),面积(平米),户型,楼层位置,总层数,建成年份,小区,小区编号,区县,商圈,标签,链接,关注人数,挂牌日期(约)\n");
+    
+    for item in data {
+        let tags = item.tags.join(";");
+        csv_content.push_str(&format!(
+            "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
+            csv_value(item.id.as_ref()),
+            item.title,
+            csv_value(item.total_price),
+            csv_value(item.unit_price),
+            csv_value(item.area),
+            item.layout,
+            csv_value(item.floor.position.map(|p| p.label())),
+            csv_value(item.floor.total_floors),
+            csv_value(item.build_year),
+            item.community,
+            csv_value(item.community_id.as_deref()),
+            csv_value(item.region.district.as_ref().map(|area| &area.name)),
+            csv_value(item.region.bizcircle.as_ref().map(|area| &area.name)),
+            tags,
+            item.url,
+            csv_value(item.follower_count),
+            csv_value(item.listed_on)
+        ));
+    }
+    
+    fs::write(filename, csv_content)?;
+    Ok(())
+}
+
+pub fn save_xiaoqu_to_csv(data: &[XiaoquData], filename: &Path) -> Result<()> {
+    let mut csv_content = String::new();
+    csv_content.push_str("小区编号,小区,区县,商圈,参考均价(元/平),建成年份,建筑类型,在售(套),近90天成交(套),出租(套),链接\n");
+
+    for item in data {
+        csv_content.push_str(&format!(
+            "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
+            csv_value(item.id.as_deref()),
+            item.name,
+            csv_value(item.region.district.as_ref().map(|area| &area.name)),
+            csv_value(item.region.bizcircle.as_ref().map(|area| &area.name)),
+            csv_value(item.average_price),
+            csv_value(item.build_year),
+            item.building_type,
+            csv_value(item.on_sale_count),
+            csv_value(item.sold_count),
+            csv_value(item.renting_count),
+            item.url
+        ));
+    }
+
+    fs::write(filename, csv_content)?;
+    Ok(())
+}
+
+/// 只包含详情页上的主要字段，完整的小区信息见 JSON
+pub fn save_xiaoqu_detail_to_csv(data: &[XiaoquRecord], filename: &Path) -> Result<()> {
+    let mut csv_content = String::new();
+    csv_content.push_str("小区编号,小区,参考均价(元/平),建成年份,建筑类型,楼栋总数,房屋总数,开发商,物业公司,物业费下限(元/平/月),物业费上限(元/平/月),链接\n");
+
+    for record in data {
+        let item = &record.summary;
+        let detail = record.detail.as_ref();
+        csv_content.push_str(&format!(
+            "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
+            csv_value(item.id.as_deref().or(detail.and_then(|d| d.community_id.as_deref()))),
+            item.name,
+            csv_value(detail.and_then(|d| d.average_price).or(item.average_price)),
+            csv_value(detail.and_then(|d| d.build_year).or(item.build_year)),
+            csv_value(detail.and_then(|d| d.building_type.as_deref())),
+            csv_value(detail.and_then(|d| d.building_count)),
+            csv_value(detail.and_then(|d| d.household_count)),
+            csv_value(detail.and_then(|d| d.developer.as_deref())),
+            csv_value(detail.and_then(|d| d.property_company.as_deref())),
+            csv_value(detail.and_then(|d| d.property_fee_min)),
+            csv_value(detail.and_then(|d| d.property_fee_max)),
+            item.url
+        ));
+    }
+
+    fs::write(filename, csv_content)?;
+    Ok(())
+}
+
+pub fn save_zufang_to_csv(data: &[ZufangData], filename: &Path) -> Result<()> {
+    let mut csv_content = String::new();
+    csv_content.push_str("编号,标题,出租方式,月租金(元),面积(平米),每平米月租金(元),户型,朝向,楼层位置,总层数,小区,小区编号,区县,商圈,标签,链接,维护日期(约)\n");
+
+    for item in data {
+        let tags = item.tags.join(";");
+        csv_content.push_str(&format!(
+            "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
+            csv_value(item.id.as_ref()),
+            item.title,
+            csv_value(item.rental_type.map(|t| t.label())),
+            csv_value(item.rent),
+            csv_value(item.area),
+            csv_value(item.rent_per_square_meter().map(|rent| format!("{:.2}", rent))),
+            item.layout,
+            item.orientation,
+            csv_value(item.floor.position.map(|p| p.label())),
+            csv_value(item.floor.total_floors),
+            item.community,
+            csv_value(item.community_id.as_deref()),
+            csv_value(item.region.district.as_ref().map(|area| &area.name)),
+            csv_value(item.region.bizcircle.as_ref().map(|area| &area.name)),
+            tags,
+            item.url,
+            csv_value(item.listed_on)
+        ));
+    }
+
+    fs::write(filename, csv_content)?;
+    Ok(())
+}
+
+pub fn save_loupan_to_csv(data: &[LoupanData], filename: &Path) -> Result<()> {
+    let mut csv_content = String::new();
+    csv_content.push_str("楼盘编号,楼盘,物业类型,销售状态,区县,商圈,地址,均价(元/平),最低总价(万),最高总价(万),户型,最小面积(平米),最大面积(平米),标签,链接\n");
+
+    for item in data {
+        csv_content.push_str(&format!(
+            "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
+            csv_value(item.id.as_deref()),
+            item.name,
+            item.property_type,
+            csv_value(item.sale_status.map(|s| s.label())),
+            csv_value(item.region.district.as_ref().map(|area| &area.name)),
+            csv_value(item.region.bizcircle.as_ref().map(|area| &area.name)),
+            item.address,
+            csv_value(item.average_price),
+            csv_value(item.total_price_min),
+            csv_value(item.total_price_max),
+            item.unit_types.join(";"),
+            csv_value(item.area_min),
+            csv_value(item.area_max),
+            item.tags.join(";"),
+            item.url
+        ));
+    }
+
+    fs::write(filename, csv_content)?;
+    Ok(())
+}
+
+/// 只包含详情页上的主要字段，完整的楼盘详情见 JSON
+pub fn save_loupan_detail_to_csv(data: &[LoupanRecord], filename: &Path) -> Result<()> {
+    let mut csv_content = String::new();
+    csv_content.push_str("楼盘编号,楼盘,均价(元/平),开发商,最新开盘,交房时间,产权年限,规划户数,物业公司,物业费(元/平/月),容积率,绿化率(%),链接\n");
+
+    for record in data {
+        let item = &record.summary;
+        let detail = record.detail.as_ref();
+        csv_content.push_str(&format!(
+            "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
+            csv_value(item.id.as_deref().or(detail.and_then(|d| d.project_id.as_deref()))),
+            item.name,
+            csv_value(item.average_price),
+            csv_value(detail.and_then(|d| d.developer.as_deref())),
+            csv_value(detail.and_then(|d| d.opening_date)),
+            csv_value(detail.and_then(|d| d.delivery_date)),
+            csv_value(detail.and_then(|d| d.property_right_years)),
+            csv_value(detail.and_then(|d| d.planned_households)),
+            csv_value(detail.and_then(|d| d.property_company.as_deref())),
+            csv_value(detail.and_then(|d| d.property_fee)),
+            csv_value(detail.and_then(|d| d.plot_ratio)),
+            csv_value(detail.and_then(|d| d.greening_rate)),
+            item.url
+        ));
+    }
+
+    fs::write(filename, csv_content)?;
+    Ok(())
+}
+
+/// 只有关联记录的数量、平均单价和租金回报率，关联的房源、成交和租房编号见 JSON
+pub fn save_community_to_csv(data: &[&Community], filename: &Path) -> Result<()> {
+    let mut csv_content = String::new();
+    csv_content.push_str("小区编号,小区,区县,商圈,参考均价(元/平),建成年份,楼栋总数,房屋总数,开发商,物业费下限(元/平/月),物业费上限(元/平/月),在售(套),近90天成交(套),关联在售(条),在售均价(元/平),关联成交(条),成交均价(元/平),关联租房(条),整租租金(元/平/月),租金回报率(%)\n");
+
+    for community in data {
+        csv_content.push_str(&format!(
+            "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
+            community.id,
+            community.name,
+            csv_value(community.region.district.as_ref().map(|area| &area.name)),
+            csv_value(community.region.bizcircle.as_ref().map(|area| &area.name)),
+            csv_value(community.average_price),
+            csv_value(community.build_year),
+            csv_value(community.building_count),
+            csv_value(community.household_count),
+            csv_value(community.developer.as_deref()),
+            csv_value(community.property_fee_min),
+            csv_value(community.property_fee_max),
+            csv_value(community.on_sale_count),
+            csv_value(community.sold_count),
+            community.listings.len(),
+            csv_value(community.listing_unit_price),
+            community.deals.len(),
+            csv_value(community.deal_unit_price),
+            community.rentals.len(),
+            csv_value(community.rent_per_square_meter),
+            csv_value(community.rental_yield)
+        ));
+    }
+
+    fs::write(filename, csv_content)?;
+    Ok(())
+}
+
+pub fn save_price_cut_to_csv(data: &[PriceChangeSummary], filename: &Path) -> Result<()> {
+    let mut csv_content = String::new();
+    csv_content.push_str("编号,标题,小区,首次出现,最近出现,首次总价(万),最新总价(万),降价(次),累计降价(万),累计降幅(%),最近降价时间,关注(人),链接\n");
+
+    for item in data {
+        csv_content.push_str(&format!(
+            "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
+            item.id,
+            item.title,
+            item.community,
+            csv_value(item.first_seen.map(|time| time.format("%Y-%m-%d %H:%M"))),
+            csv_value(item.last_seen.map(|time| time.format("%Y-%m-%d %H:%M"))),
+            csv_value(item.first_price),
+            csv_value(item.latest_price),
+            item.cut_count,
+            csv_value(item.cumulative_discount),
+            csv_value(item.cumulative_discount_percent),
+            csv_value(item.cuts.last().map(|cut| cut.observed_at.format("%Y-%m-%d %H:%M"))),
+            csv_value(item.follower_count),
+            item.url
+        ));
+    }
+
+    fs::write(filename, csv_content)?;
+    Ok(())
+}
+
+/// 缺失的值在CSV中留空
+fn csv_value<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
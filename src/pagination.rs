@@ -0,0 +1,193 @@
+use crate::filter::{Range, SearchFilter};
+use crate::search_url::{FilterSegment, ListingKind, SearchUrl};
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+/// 贝壳网列表最多只能翻到第100页
+pub const MAX_PAGES: u32 = 100;
+
+/// 成交、在售和租房列表每页30条
+pub const PAGE_SIZE: u32 = 30;
+
+/// 总价切片（万），相邻区间共享端点，避免带小数的价格落在缝隙里，重复的记录在汇总时去重
+const PRICE_SLICES: &[(Option<u32>, Option<u32>)] = &[
+    (None, Some(50)),
+    (Some(50), Some(80)),
+    (Some(80), Some(100)),
+    (Some(100), Some(120)),
+    (Some(120), Some(150)),
+    (Some(150), Some(200)),
+    (Some(200), Some(300)),
+    (Some(300), Some(500)),
+    (Some(500), None),
+];
+
+/// 面积切片（平米），规则同上
+const AREA_SLICES: &[(Option<u32>, Option<u32>)] = &[
+    (None, Some(50)),
+    (Some(50), Some(70)),
+    (Some(70), Some(90)),
+    (Some(90), Some(110)),
+    (Some(110), Some(130)),
+    (Some(130), Some(150)),
+    (Some(150), Some(200)),
+    (Some(200), None),
+];
+
+/// 列表页上的结果总数和分页信息
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageInfo {
+    /// "共找到 N 套" 中的 N
+    pub total_count: Option<u32>,
+    /// 分页组件 page-data（租房列表为 data-totalpage）中的总页数
+    pub total_pages: Option<u32>,
+    pub cur_page: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageData {
+    total_page: u32,
+    cur_page: Option<u32>,
+}
+
+impl PageInfo {
+    pub fn parse(document: &Html) -> Self {
+        let mut info = PageInfo::default();
+
+        // 结果总数：<div class="total fl">共找到<span> 123 </span>套...</div>
+        let total_selector = Selector::parse(".total span").unwrap();
+        if let Some(el) = document.select(&total_selector).next() {
+            info.total_count = el.text().collect::<String>().trim().parse().ok();
+        }
+
+        // 分页组件：<div class="page-box house-lst-page-box" page-data='{"totalPage":5,"curPage":1}'>
+        let page_selector = Selector::parse("[page-data]").unwrap();
+        if let Some(data) = document
+            .select(&page_selector)
+            .next()
+            .and_then(|el| el.value().attr("page-data"))
+            .and_then(|data| serde_json::from_str::<PageData>(data).ok())
+        {
+            info.total_pages = Some(data.total_page);
+            info.cur_page = data.cur_page;
+        }
+
+        // 租房列表：<span class="content__title--hl">56</span> 和 <div class="content__pg" data-totalpage="2" data-curpage="1">
+        if info.total_count.is_none() {
+            let total_selector = Selector::parse(".content__title--hl").unwrap();
+            info.total_count = document.select(&total_selector)
+                .next()
+                .and_then(|el| el.text().collect::<String>().trim().parse().ok());
+        }
+        if info.total_pages.is_none() {
+            let page_selector = Selector::parse("[data-totalpage]").unwrap();
+            if let Some(el) = document.select(&page_selector).next() {
+                info.total_pages = el.value().attr("data-totalpage").and_then(|v| v.trim().parse().ok());
+                info.cur_page = el.value().attr("data-curpage").and_then(|v| v.trim().parse().ok());
+            }
+        }
+
+        // 新房列表每页只有10条，总页数按分页组件上的每页条数计算：
+        // <div class="page-box" data-current="1" data-total-count="56" data-page-size="10">
+        if info.total_pages.is_none() {
+            let page_selector = Selector::parse("[data-total-count]").unwrap();
+            if let Some(el) = document.select(&page_selector).next() {
+                let attr = |name: &str| el.value().attr(name).and_then(|v| v.trim().parse::<u32>().ok());
+                info.total_count = info.total_count.or(attr("data-total-count"));
+                info.total_pages = match (info.total_count, attr("data-page-size")) {
+                    (Some(count), Some(size)) if size > 0 => Some(count.div_ceil(size)),
+                    _ => None,
+                };
+                info.cur_page = attr("data-current");
+            }
+        }
+
+        info
+    }
+
+    /// 实际可以翻到的页数，不超过网站的100页上限；页面上没有分页信息时返回 None
+    pub fn page_count(&self) -> Option<u32> {
+        let pages = match (self.total_pages, self.total_count) {
+            (Some(pages), _) => pages,
+            (None, Some(count)) => count.div_ceil(PAGE_SIZE),
+            (None, None) => return None,
+        };
+        Some(pages.min(MAX_PAGES))
+    }
+
+    /// 结果数超过100页能展示的数量，需要拆分查询才能拿到全部房源
+    pub fn is_truncated(&self) -> bool {
+        match self.total_count {
+            Some(count) => count > MAX_PAGES * PAGE_SIZE,
+            None => self.total_pages.is_some_and(|pages| pages > MAX_PAGES),
+        }
+    }
+}
+
+/// 把超过100页上限的查询拆成更细的切片
+///
+/// 依次尝试：按总价切片、按面积切片、把已有的总价区间对半分、把已有的面积区间对半分。
+/// 返回切片维度的名称和拆分后的查询，无法继续拆分时返回 None。
+pub fn split_search(search: &SearchUrl) -> Option<(&'static str, Vec<SearchUrl>)> {
+    // 小区、租房和新房列表的筛选段与二手房不同，无法按总价、面积拆分
+    if matches!(search.kind, ListingKind::Xiaoqu | ListingKind::Zufang | ListingKind::Loupan) {
+        return None;
+    }
+    let filter = SearchFilter::from_segments(&search.filters);
+    let has_price_band = filter.extra.iter().any(|s| matches!(s, FilterSegment::PriceBand(_)));
+    let has_area_band = filter.extra.iter().any(|s| matches!(s, FilterSegment::AreaBand(_)));
+
+    let (dimension, slices): (&'static str, Vec<SearchFilter>) = if filter.total_price.is_none() && !has_price_band {
+        ("总价", PRICE_SLICES.iter().map(|(min, max)| SearchFilter {
+            total_price: Some(Range { min: *min, max: *max }),
+            ..filter.clone()
+        }).collect())
+    } else if filter.area.is_none() && !has_area_band {
+        ("面积", AREA_SLICES.iter().map(|(min, max)| SearchFilter {
+            area: Some(Range { min: *min, max: *max }),
+            ..filter.clone()
+        }).collect())
+    } else if let Some((low, high)) = filter.total_price.and_then(bisect) {
+        ("总价", vec![
+            SearchFilter { total_price: Some(low), ..filter.clone() },
+            SearchFilter { total_price: Some(high), ..filter.clone() },
+        ])
+    } else if let Some((low, high)) = filter.area.and_then(bisect) {
+        ("面积", vec![
+            SearchFilter { area: Some(low), ..filter.clone() },
+            SearchFilter { area: Some(high), ..filter.clone() },
+        ])
+    } else {
+        return None;
+    };
+
+    let searches = slices
+        .into_iter()
+        .map(|slice| SearchUrl { filters: slice.segments(), ..search.clone() })
+        .collect();
+
+    Some((dimension, searches))
+}
+
+/// 把区间对半分，两半共享中点
+fn bisect(range: Range) -> Option<(Range, Range)> {
+    let min = range.min.unwrap_or(0);
+    match range.max {
+        Some(max) if max > min + 1 => {
+            let mid = min + (max - min) / 2;
+            Some((
+                Range { min: range.min, max: Some(mid) },
+                Range { min: Some(mid), max: Some(max) },
+            ))
+        }
+        Some(_) => None,
+        None => {
+            let mid = min.max(1) * 2;
+            Some((
+                Range { min: range.min, max: Some(mid) },
+                Range { min: Some(mid), max: None },
+            ))
+        }
+    }
+}
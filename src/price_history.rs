@@ -0,0 +1,296 @@
+use crate::ershoufang::ErshoufangData;
+use crate::listing::ListingId;
+use crate::output::{save_outputs, save_price_cut_to_csv, OutputFormat};
+use crate::text::or_unknown;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 一次爬取时看到的价格和关注人数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceObservation {
+    /// 爬取时间
+    pub observed_at: DateTime<Local>,
+    /// 总价（万元）
+    pub total_price: Option<f64>,
+    /// 单价（元/平米）
+    pub unit_price: Option<f64>,
+    /// 关注人数
+    pub follower_count: Option<u32>,
+}
+
+/// 一次降价：相邻两次有总价的观察之间总价下降
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceCut {
+    pub listing_id: ListingId,
+    /// 第一次看到降价后总价的爬取时间，实际降价发生在上一次爬取和这一次之间
+    pub observed_at: DateTime<Local>,
+    /// 降价前的总价（万元）
+    pub previous_price: f64,
+    /// 降价后的总价（万元）
+    pub price: f64,
+}
+
+impl PriceCut {
+    /// 降价金额（万元）
+    pub fn amount(&self) -> f64 {
+        round2(self.previous_price - self.price)
+    }
+}
+
+/// 一套在售房源在历次爬取中的价格记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingHistory {
+    pub id: ListingId,
+    /// 标题、小区和链接取最近一次爬取时的值
+    pub title: String,
+    pub community: String,
+    pub url: String,
+    /// 按爬取时间先后排列
+    pub observations: Vec<PriceObservation>,
+}
+
+impl ListingHistory {
+    pub fn first_seen(&self) -> Option<DateTime<Local>> {
+        self.observations.first().map(|observation| observation.observed_at)
+    }
+
+    pub fn last_seen(&self) -> Option<DateTime<Local>> {
+        self.observations.last().map(|observation| observation.observed_at)
+    }
+
+    /// 最近一次爬取时的价格
+    pub fn latest(&self) -> Option<&PriceObservation> {
+        self.observations.last()
+    }
+
+    /// 首次看到时的总价（万元），首次没有总价时取之后第一个有总价的观察
+    pub fn first_price(&self) -> Option<f64> {
+        self.observations.iter().find_map(|observation| observation.total_price)
+    }
+
+    /// 最近一次的总价（万元）
+    pub fn latest_price(&self) -> Option<f64> {
+        self.observations.iter().rev().find_map(|observation| observation.total_price)
+    }
+
+    /// 历次降价，没有总价的观察（如暂未公开）被跳过，不算作降价或涨价
+    pub fn price_cuts(&self) -> Vec<PriceCut> {
+        let mut cuts = Vec::new();
+        let mut previous: Option<f64> = None;
+        for observation in &self.observations {
+            let Some(price) = observation.total_price else {
+                continue;
+            };
+            if let Some(previous_price) = previous.filter(|previous_price| price < *previous_price) {
+                cuts.push(PriceCut {
+                    listing_id: self.id.clone(),
+                    observed_at: observation.observed_at,
+                    previous_price,
+                    price,
+                });
+            }
+            previous = Some(price);
+        }
+        cuts
+    }
+
+    pub fn cut_count(&self) -> usize {
+        self.price_cuts().len()
+    }
+
+    /// 首次看到以来的累计降价（万元），即首次总价减去最近总价，先降后涨的部分会相互抵消，净涨价时为负
+    pub fn cumulative_discount(&self) -> Option<f64> {
+        Some(round2(self.first_price()? - self.latest_price()?))
+    }
+
+    /// 累计降价占首次总价的百分比
+    pub fn cumulative_discount_percent(&self) -> Option<f64> {
+        let first_price = self.first_price().filter(|price| *price > 0.0)?;
+        Some(round2(self.cumulative_discount()? / first_price * 100.0))
+    }
+
+    /// 用于输出报告的调价汇总
+    pub fn summary(&self) -> PriceChangeSummary {
+        PriceChangeSummary {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            community: self.community.clone(),
+            url: self.url.clone(),
+            first_seen: self.first_seen(),
+            last_seen: self.last_seen(),
+            first_price: self.first_price(),
+            latest_price: self.latest_price(),
+            follower_count: self.latest().and_then(|observation| observation.follower_count),
+            cut_count: self.cut_count(),
+            cumulative_discount: self.cumulative_discount(),
+            cumulative_discount_percent: self.cumulative_discount_percent(),
+            cuts: self.price_cuts(),
+        }
+    }
+}
+
+/// 一套房源的调价汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceChangeSummary {
+    pub id: ListingId,
+    pub title: String,
+    pub community: String,
+    pub url: String,
+    pub first_seen: Option<DateTime<Local>>,
+    pub last_seen: Option<DateTime<Local>>,
+    /// 首次总价（万元）
+    pub first_price: Option<f64>,
+    /// 最近总价（万元）
+    pub latest_price: Option<f64>,
+    /// 最近一次的关注人数
+    pub follower_count: Option<u32>,
+    /// 降价次数
+    pub cut_count: usize,
+    /// 累计降价（万元）
+    pub cumulative_discount: Option<f64>,
+    /// 累计降幅（%）
+    pub cumulative_discount_percent: Option<f64>,
+    pub cuts: Vec<PriceCut>,
+}
+
+/// 以房源编号为键、跨多次运行累积的在售价格历史，保存在一个 JSON 文件中
+///
+/// 每次爬取在售数据后追加本次看到的总价、单价和关注人数，
+/// 输出的 `<name>_data.json` 每次都会被覆盖，价格变化只能从这里查到。
+#[derive(Debug)]
+pub struct PriceHistory {
+    path: PathBuf,
+    pub listings: BTreeMap<String, ListingHistory>,
+}
+
+impl PriceHistory {
+    /// 读取 `path` 中的价格历史，文件不存在时从空历史开始
+    pub fn open(path: &Path) -> Result<Self> {
+        let listings = if path.exists() {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("读取价格历史失败: {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("解析价格历史失败: {}", path.display()))?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self { path: path.to_path_buf(), listings })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ListingHistory> {
+        self.listings.get(id)
+    }
+
+    /// 记录 `observed_at` 这次爬取看到的在售房源，返回记录的条数
+    ///
+    /// 没有编号的房源无法在不同批次之间对应，会被跳过；
+    /// 同一房源在同一时间已有记录时（如重复记录同一批数据）覆盖该记录。
+    pub fn record(&mut self, items: &[ErshoufangData], observed_at: DateTime<Local>) -> usize {
+        let mut recorded = 0;
+        for item in items {
+            let Some(id) = &item.id else {
+                continue;
+            };
+            let history = self.listings.entry(id.as_str().to_string()).or_insert_with(|| ListingHistory {
+                id: id.clone(),
+                title: String::new(),
+                community: String::new(),
+                url: String::new(),
+                observations: Vec::new(),
+            });
+            history.title = item.title.clone();
+            history.community = item.community.clone();
+            history.url = item.url.clone();
+
+            let observation = PriceObservation {
+                observed_at,
+                total_price: item.total_price,
+                unit_price: item.unit_price,
+                follower_count: item.follower_count,
+            };
+            match history.observations.iter_mut().find(|existing| existing.observed_at == observed_at) {
+                Some(existing) => *existing = observation,
+                None => {
+                    history.observations.push(observation);
+                    history.observations.sort_by_key(|observation| observation.observed_at);
+                }
+            }
+            recorded += 1;
+        }
+        recorded
+    }
+
+    /// 所有房源的降价，按爬取时间先后排列
+    pub fn price_cuts(&self) -> Vec<PriceCut> {
+        let mut cuts: Vec<PriceCut> = self.listings.values().flat_map(ListingHistory::price_cuts).collect();
+        cuts.sort_by_key(|cut| cut.observed_at);
+        cuts
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        // 价格历史无法重新爬取，先写临时文件再重命名，避免进程中途退出时损坏已有的历史
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(&self.listings)?)?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("保存价格历史失败: {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+/// 保留两位小数
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// 读取价格历史，把降价次数不少于 `min_cuts` 的房源按累计降价从多到少保存为 `<stem>.json` / `<stem>.csv`
+pub fn report_price_cuts(history_path: &Path, min_cuts: usize, output_dir: &Path, stem: &str, formats: &[OutputFormat]) -> Result<()> {
+    if !history_path.exists() {
+        return Err(anyhow::anyhow!("价格历史不存在: {}", history_path.display()));
+    }
+    let history = PriceHistory::open(history_path)?;
+    println!("价格历史共 {} 套房源", history.listings.len());
+
+    let mut summaries: Vec<PriceChangeSummary> = history.listings
+        .values()
+        .map(ListingHistory::summary)
+        .filter(|summary| summary.cut_count >= min_cuts)
+        .collect();
+    summaries.sort_by(|a, b| {
+        b.cumulative_discount
+            .unwrap_or(f64::MIN)
+            .total_cmp(&a.cumulative_discount.unwrap_or(f64::MIN))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    println!("\n=== 降价房源 ===");
+    println!("降价至少 {} 次的房源: {} 套", min_cuts, summaries.len());
+    for summary in summaries.iter().take(10) {
+        println!(
+            "  {} {} [{}]: {} -> {}，降价 {} 次，累计 {} ({})",
+            summary.id,
+            summary.title,
+            summary.community,
+            or_unknown(summary.first_price, "万"),
+            or_unknown(summary.latest_price, "万"),
+            summary.cut_count,
+            or_unknown(summary.cumulative_discount, "万"),
+            or_unknown(summary.cumulative_discount_percent, "%"),
+        );
+    }
+    if summaries.len() > 10 {
+        println!("  ... 还有 {} 套", summaries.len() - 10);
+    }
+
+    save_outputs(&summaries, output_dir, stem, formats, "降价房源", save_price_cut_to_csv)
+}
@@ -0,0 +1,95 @@
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 单个站点的限速配置：令牌桶控制平均速率和突发量，另外每两次请求之间随机等待一段时间
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RateLimit {
+    /// 每分钟最多请求数（令牌补充速率）
+    pub requests_per_minute: u32,
+    /// 令牌桶容量，允许短时间内连续发出的请求数
+    pub burst: u32,
+    /// 两次请求之间的最短等待时间（毫秒）
+    pub min_delay_ms: u64,
+    /// 两次请求之间的最长等待时间（毫秒），实际等待在 min 和 max 之间随机
+    pub max_delay_ms: u64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 30,
+            burst: 3,
+            min_delay_ms: 1_000,
+            max_delay_ms: 3_000,
+        }
+    }
+}
+
+impl RateLimit {
+    fn refill_per_sec(&self) -> f64 {
+        self.requests_per_minute.max(1) as f64 / 60.0
+    }
+
+    fn capacity(&self) -> f64 {
+        self.burst.max(1) as f64
+    }
+
+    fn random_delay(&self) -> Duration {
+        let min = self.min_delay_ms.min(self.max_delay_ms);
+        let max = self.min_delay_ms.max(self.max_delay_ms);
+        Duration::from_millis(rand::thread_rng().gen_range(min..=max))
+    }
+}
+
+/// 每个 host 一个令牌桶
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    /// 下一次请求最早可以发出的时间
+    next_allowed: Instant,
+}
+
+/// 按 host 限速，所有克隆出来的 [`crate::fetch::Fetcher`] 共用同一个实例，
+/// 这样多个任务或并发请求访问同一站点时加起来也不会超过限速
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    /// 等待直到可以向 `host` 发出下一个请求
+    pub async fn acquire(&self, host: &str, limit: &RateLimit) {
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap();
+                let now = Instant::now();
+                let bucket = buckets.entry(host.to_string()).or_insert_with(|| Bucket {
+                    tokens: limit.capacity(),
+                    last_refill: now,
+                    next_allowed: now,
+                });
+
+                let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * limit.refill_per_sec()).min(limit.capacity());
+                bucket.last_refill = now;
+
+                if now < bucket.next_allowed {
+                    bucket.next_allowed - now
+                } else if bucket.tokens < 1.0 {
+                    Duration::from_secs_f64((1.0 - bucket.tokens) / limit.refill_per_sec())
+                } else {
+                    bucket.tokens -= 1.0;
+                    bucket.next_allowed = now + limit.random_delay();
+                    return;
+                }
+            };
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+/// 区县或商圈：列表页地址中的拼音标识和页面上显示的名称
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Area {
+    /// 拼音标识，如 "yubei"、"dazhulin"；新房列表上的区域只有名称，筛选栏中找不到时为空
+    pub slug: String,
+    /// 名称，如 "渝北"、"大竹林"
+    pub name: String,
+}
+
+impl Area {
+    /// 从 `<a href="https://cq.ke.com/ershoufang/dazhulin/">大竹林</a>` 这样的区域链接解析
+    pub fn from_link(link: &ElementRef) -> Option<Self> {
+        let slug = area_slug(link.value().attr("href")?)?;
+        let name = link.text().collect::<String>().trim().to_string();
+        (!name.is_empty()).then_some(Self { slug, name })
+    }
+}
+
+/// 记录所在的区域：城市 → 区县 → 商圈，无法确定的层级为 None
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    /// 城市子域名，如 "cq"
+    pub city: Option<String>,
+    pub district: Option<Area>,
+    pub bizcircle: Option<Area>,
+}
+
+impl Region {
+    pub fn district_name(&self) -> &str {
+        self.district.as_ref().map_or("未知", |area| area.name.as_str())
+    }
+
+    pub fn bizcircle_name(&self) -> &str {
+        self.bizcircle.as_ref().map_or("未知", |area| area.name.as_str())
+    }
+
+    /// 如 "渝北/大竹林"，缺少的层级显示为 "未知"
+    pub fn describe(&self) -> String {
+        format!("{}/{}", self.district_name(), self.bizcircle_name())
+    }
+}
+
+/// 一个区县及其下的商圈
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DistrictNode {
+    pub district: Area,
+    pub bizcircles: Vec<Area>,
+}
+
+/// 城市 → 区县 → 商圈 的层级
+///
+/// 列表页的区域筛选栏只列出所有区县和当前选中区县下的商圈，
+/// 没有选中区县时层级中只有区县，商圈列表为空。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionTree {
+    pub city: Option<String>,
+    pub districts: Vec<DistrictNode>,
+}
+
+impl RegionTree {
+    /// 商圈所属的区县
+    pub fn district_of(&self, bizcircle: &str) -> Option<&Area> {
+        self.districts
+            .iter()
+            .find(|node| node.bizcircles.iter().any(|area| area.slug == bizcircle))
+            .map(|node| &node.district)
+    }
+
+    /// 按拼音标识查找区县或商圈，返回它在层级中的完整位置
+    pub fn locate(&self, slug: &str) -> Option<Region> {
+        if let Some(node) = self.districts.iter().find(|node| node.district.slug == slug) {
+            return Some(Region { city: self.city.clone(), district: Some(node.district.clone()), bizcircle: None });
+        }
+        self.districts.iter().find_map(|node| {
+            let bizcircle = node.bizcircles.iter().find(|area| area.slug == slug)?;
+            Some(Region {
+                city: self.city.clone(),
+                district: Some(node.district.clone()),
+                bizcircle: Some(bizcircle.clone()),
+            })
+        })
+    }
+}
+
+/// 列表页上的区域筛选栏：区域层级，以及当前选中（即任务所爬取）的区域
+///
+/// ```html
+/// <div data-role="ershoufang">
+///   <div><a href="/ershoufang/jiangbei/">江北</a><a href="/ershoufang/yubei/" class="selected">渝北</a></div>
+///   <div><a href="/ershoufang/dazhulin/" class="selected">大竹林</a><a href="/ershoufang/lijia/">礼嘉</a></div>
+/// </div>
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RegionFilter {
+    pub tree: RegionTree,
+    pub selected: Region,
+}
+
+impl RegionFilter {
+    /// 解析 `channel`（如 "ershoufang"）列表页上的筛选栏，页面上没有筛选栏时层级为空
+    pub fn parse(document: &Html, channel: &str) -> Self {
+        let level_selector = Selector::parse(&format!(r#"[data-role="{}"] > div"#, channel)).unwrap();
+        let link_selector = Selector::parse("a").unwrap();
+
+        let mut levels = document.select(&level_selector).map(|level| {
+            level.select(&link_selector)
+                .filter_map(|link| {
+                    let area = Area::from_link(&link)?;
+                    let selected = link.value().classes().any(|class| class == "selected");
+                    Some((area, selected))
+                })
+                .collect::<Vec<_>>()
+        });
+        let districts = levels.next().unwrap_or_default();
+        let bizcircles = levels.next().unwrap_or_default();
+
+        let selected_district = districts.iter().find(|(_, selected)| *selected).map(|(area, _)| area.clone());
+        let selected_bizcircle = bizcircles.iter().find(|(_, selected)| *selected).map(|(area, _)| area.clone());
+
+        // 页面上的链接都是相对地址，城市从页面中任意一个贝壳网绝对链接取
+        let city = document.select(&link_selector)
+            .filter_map(|link| link.value().attr("href"))
+            .find_map(city_of);
+
+        let tree = RegionTree {
+            city: city.clone(),
+            districts: districts
+                .into_iter()
+                .map(|(district, _)| {
+                    let bizcircles = if selected_district.as_ref() == Some(&district) {
+                        bizcircles.iter().map(|(area, _)| area.clone()).collect()
+                    } else {
+                        Vec::new()
+                    };
+                    DistrictNode { district, bizcircles }
+                })
+                .collect(),
+        };
+
+        Self {
+            tree,
+            selected: Region { city, district: selected_district, bizcircle: selected_bizcircle },
+        }
+    }
+
+    /// 列表项所在的区域
+    ///
+    /// 商圈和城市取自列表项自身的链接，区县在筛选栏的层级中查找；
+    /// 都找不到时使用筛选栏中选中的区域，因为列表页上只会出现所选区域内的房源。
+    pub fn locate(&self, bizcircle: Option<Area>, city: Option<String>) -> Region {
+        let district = bizcircle.as_ref()
+            .and_then(|area| self.tree.district_of(&area.slug))
+            .cloned()
+            .or_else(|| self.selected.district.clone());
+        Region {
+            city: city.or_else(|| self.selected.city.clone()),
+            district,
+            bizcircle: bizcircle.or_else(|| self.selected.bizcircle.clone()),
+        }
+    }
+}
+
+/// `https://cq.ke.com/ershoufang/dazhulin/` 或 `/ershoufang/dazhulin/pg2/` 中的区域标识 dazhulin
+///
+/// 区域标识只含小写字母，小区链接 `/xiaoqu/3611056497876/` 之类不算。
+pub fn area_slug(href: &str) -> Option<String> {
+    let path = href.split(['?', '#']).next()?;
+    let path = match path.split_once("://") {
+        Some((_, rest)) => rest.split_once('/').map_or("", |(_, path)| path),
+        None => path,
+    };
+    let mut parts = path.split('/').filter(|part| !part.is_empty());
+    let _channel = parts.next()?;
+    let slug = parts.next()?;
+    slug.chars().all(|c| c.is_ascii_lowercase()).then(|| slug.to_string())
+}
+
+/// `https://cq.ke.com/...`、`https://cq.zu.ke.com/...` 中的城市子域名 cq
+pub fn city_of(href: &str) -> Option<String> {
+    let (_, rest) = href.split_once("://")?;
+    let host = rest.split(['/', '?', '#']).next()?;
+    let city = host.strip_suffix(".ke.com")?.split('.').next()?;
+    (!city.is_empty()).then(|| city.to_string())
+}
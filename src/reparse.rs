@@ -0,0 +1,143 @@
+use crate::archive::Archive;
+use crate::chengjiao::{ChengjiaoData, ChengjiaoScraper};
+use crate::crawl::ListScraper;
+use crate::ershoufang::{ErshoufangData, ErshoufangScraper};
+use crate::fetch::Fetcher;
+use crate::loupan::{LoupanData, LoupanScraper};
+use crate::output::{
+    save_chengjiao_to_csv, save_ershoufang_to_csv, save_loupan_to_csv, save_outputs, save_xiaoqu_to_csv, save_zufang_to_csv,
+    OutputFormat,
+};
+use crate::search_url::{ListingKind, SearchUrl};
+use crate::xiaoqu::{XiaoquData, XiaoquScraper};
+use crate::zufang::{ZufangData, ZufangScraper};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 一次重新解析的页面统计
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReparseSummary {
+    /// 按列表页重新解析的页面数
+    pub parsed_pages: usize,
+    /// 详情页等不是列表页、被跳过的页面数
+    pub skipped_pages: usize,
+}
+
+/// 同一类型的记录：按记录键去重，同一记录出现在多个快照中时保留最新抓取的一份
+///
+/// 归档按抓取时间从旧到新读取，后出现的快照覆盖先前的记录，记录顺序保持第一次出现的位置。
+struct Latest<T> {
+    items: Vec<T>,
+    index: HashMap<String, usize>,
+}
+
+impl<T> Latest<T> {
+    fn new() -> Self {
+        Self { items: Vec::new(), index: HashMap::new() }
+    }
+
+    fn insert(&mut self, key: String, item: T) {
+        match self.index.get(&key) {
+            Some(&i) => self.items[i] = item,
+            None => {
+                self.index.insert(key, self.items.len());
+                self.items.push(item);
+            }
+        }
+    }
+}
+
+/// 用当前的解析逻辑重新解析归档中的所有列表页，不发起任何网络请求
+///
+/// 结果按页面类型保存为 `reparse_chengjiao_data` / `reparse_ershoufang_data` / `reparse_xiaoqu_data` /
+/// `reparse_zufang_data` / `reparse_loupan_data`，同一记录在多个快照中出现时取最新的一次抓取。
+/// 归档中的详情页不是列表页，会被跳过。
+pub fn reparse_archive(archive: &Archive, output_dir: &Path, formats: &[OutputFormat]) -> Result<ReparseSummary> {
+    let pages = archive.load_all()?;
+    println!("归档目录 {} 中共有 {} 个页面", archive.dir().display(), pages.len());
+
+    // 解析只用到 HTML，Fetcher 不会发出请求
+    let chengjiao_scraper = ChengjiaoScraper::new(Fetcher::new(String::new()));
+    let ershoufang_scraper = ErshoufangScraper::new(Fetcher::new(String::new()));
+    let xiaoqu_scraper = XiaoquScraper::new(Fetcher::new(String::new()));
+    let zufang_scraper = ZufangScraper::new(Fetcher::new(String::new()));
+    let loupan_scraper = LoupanScraper::new(Fetcher::new(String::new()));
+
+    let mut chengjiao: Latest<ChengjiaoData> = Latest::new();
+    let mut ershoufang: Latest<ErshoufangData> = Latest::new();
+    let mut xiaoqu: Latest<XiaoquData> = Latest::new();
+    let mut zufang: Latest<ZufangData> = Latest::new();
+    let mut loupan: Latest<LoupanData> = Latest::new();
+    let mut summary = ReparseSummary::default();
+
+    for page in &pages {
+        let kind = match SearchUrl::parse(&page.url) {
+            Ok(search) => search.kind,
+            Err(e) => {
+                println!("跳过不是列表页的归档 {}: {}", page.url, e);
+                summary.skipped_pages += 1;
+                continue;
+            }
+        };
+        summary.parsed_pages += 1;
+
+        println!("\n重新解析 {} (抓取于 {})", page.url, page.fetched_at);
+        match kind {
+            ListingKind::Chengjiao => {
+                for item in chengjiao_scraper.parse_page(&page.body).items {
+                    chengjiao.insert(ChengjiaoScraper::item_key(&item), item);
+                }
+            }
+            ListingKind::Ershoufang => {
+                for item in ershoufang_scraper.parse_page_on(&page.body, page.fetched_on()).items {
+                    ershoufang.insert(ErshoufangScraper::item_key(&item), item);
+                }
+            }
+            ListingKind::Xiaoqu => {
+                for item in xiaoqu_scraper.parse_page(&page.body).items {
+                    xiaoqu.insert(XiaoquScraper::item_key(&item), item);
+                }
+            }
+            ListingKind::Zufang => {
+                for item in zufang_scraper.parse_page_at(&page.body, &page.url, page.fetched_on()).items {
+                    zufang.insert(ZufangScraper::item_key(&item), item);
+                }
+            }
+            ListingKind::Loupan => {
+                for item in loupan_scraper.parse_page_at(&page.body, &page.url).items {
+                    loupan.insert(LoupanScraper::item_key(&item), item);
+                }
+            }
+        }
+    }
+
+    let (chengjiao, ershoufang, xiaoqu, zufang, loupan) =
+        (chengjiao.items, ershoufang.items, xiaoqu.items, zufang.items, loupan.items);
+
+    println!("\n=== 重新解析完成 ===");
+    println!("列表页: {} 个，跳过: {} 个", summary.parsed_pages, summary.skipped_pages);
+    println!("成交数据: {} 条", chengjiao.len());
+    println!("在售数据: {} 条", ershoufang.len());
+    println!("小区数据: {} 条", xiaoqu.len());
+    println!("租房数据: {} 条", zufang.len());
+    println!("新房数据: {} 条", loupan.len());
+
+    if !chengjiao.is_empty() {
+        save_outputs(&chengjiao, output_dir, "reparse_chengjiao_data", formats, "成交数据", save_chengjiao_to_csv)?;
+    }
+    if !ershoufang.is_empty() {
+        save_outputs(&ershoufang, output_dir, "reparse_ershoufang_data", formats, "在售数据", save_ershoufang_to_csv)?;
+    }
+    if !xiaoqu.is_empty() {
+        save_outputs(&xiaoqu, output_dir, "reparse_xiaoqu_data", formats, "小区数据", save_xiaoqu_to_csv)?;
+    }
+    if !zufang.is_empty() {
+        save_outputs(&zufang, output_dir, "reparse_zufang_data", formats, "租房数据", save_zufang_to_csv)?;
+    }
+    if !loupan.is_empty() {
+        save_outputs(&loupan, output_dir, "reparse_loupan_data", formats, "新房数据", save_loupan_to_csv)?;
+    }
+
+    Ok(summary)
+}
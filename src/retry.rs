@@ -0,0 +1,85 @@
+use crate::error::ScrapeError;
+use rand::Rng;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// 错误的重试类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// 网络抖动、5xx、空页等，按指数退避重试
+    Transient,
+    /// 人机验证或 429，退避的起点更长
+    Throttled,
+    /// 4xx、字段解析失败等，重试也不会成功
+    Permanent,
+    /// 需要登录，整个任务都无法继续
+    Fatal,
+}
+
+impl ScrapeError {
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            ScrapeError::Network(_) | ScrapeError::Decode(_) | ScrapeError::EmptyPage => ErrorClass::Transient,
+            ScrapeError::Http(status) if *status == StatusCode::TOO_MANY_REQUESTS => ErrorClass::Throttled,
+            ScrapeError::Http(status) if status.is_server_error() => ErrorClass::Transient,
+            ScrapeError::Http(_) | ScrapeError::Parse { .. } | ScrapeError::InvalidUrl { .. } => ErrorClass::Permanent,
+            ScrapeError::Captcha => ErrorClass::Throttled,
+            ScrapeError::LoginRequired { .. } => ErrorClass::Fatal,
+        }
+    }
+}
+
+/// 单页重试策略：最多尝试 `max_attempts` 次，每次等待时间按指数增长并加上随机抖动
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// 包含第一次请求在内的最大尝试次数
+    pub max_attempts: u32,
+    /// 临时错误第一次重试前的等待时间（毫秒）
+    pub base_delay_ms: u64,
+    /// 人机验证/限流时第一次重试前的等待时间（毫秒）
+    pub throttled_delay_ms: u64,
+    /// 单次等待的上限（毫秒）
+    pub max_delay_ms: u64,
+    /// 抖动比例，0.3 表示在计算值的 ±30% 内随机
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay_ms: 1_000,
+            throttled_delay_ms: 5_000,
+            max_delay_ms: 60_000,
+            jitter: 0.3,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 第 `attempt` 次尝试（从1开始）失败后，返回下一次重试前应等待的时间；不应再重试时返回 None
+    pub fn backoff(&self, error: &ScrapeError, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        let base_ms = match error.class() {
+            ErrorClass::Transient => self.base_delay_ms,
+            ErrorClass::Throttled => self.throttled_delay_ms,
+            ErrorClass::Permanent | ErrorClass::Fatal => return None,
+        };
+
+        let exponential = base_ms.saturating_mul(1u64 << (attempt - 1).min(16));
+        let capped = exponential.min(self.max_delay_ms) as f64;
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = if jitter > 0.0 {
+            rand::thread_rng().gen_range((1.0 - jitter)..=(1.0 + jitter))
+        } else {
+            1.0
+        };
+
+        Some(Duration::from_millis((capped * factor) as u64))
+    }
+}
@@ -0,0 +1,446 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 列表类型，对应贝壳网的 /chengjiao/、/ershoufang/、/xiaoqu/，租房站的 /zufang/ 与新房站的 /loupan/ 频道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListingKind {
+    Chengjiao,
+    Ershoufang,
+    Xiaoqu,
+    Zufang,
+    Loupan,
+}
+
+impl ListingKind {
+    pub const ALL: [ListingKind; 5] = [
+        ListingKind::Chengjiao,
+        ListingKind::Ershoufang,
+        ListingKind::Xiaoqu,
+        ListingKind::Zufang,
+        ListingKind::Loupan,
+    ];
+
+    pub fn path(&self) -> &'static str {
+        match self {
+            ListingKind::Chengjiao => "chengjiao",
+            ListingKind::Ershoufang => "ershoufang",
+            ListingKind::Xiaoqu => "xiaoqu",
+            ListingKind::Zufang => "zufang",
+            ListingKind::Loupan => "loupan",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ListingKind::Chengjiao => "成交",
+            ListingKind::Ershoufang => "在售",
+            ListingKind::Xiaoqu => "小区",
+            ListingKind::Zufang => "租房",
+            ListingKind::Loupan => "新房",
+        }
+    }
+
+    /// 频道所在的站点，租房和新房分别在单独的 `{city}.zu.ke.com`、`{city}.fang.ke.com` 上
+    pub fn domain(&self) -> &'static str {
+        match self {
+            ListingKind::Zufang => "zu.ke.com",
+            ListingKind::Loupan => "fang.ke.com",
+            _ => "ke.com",
+        }
+    }
+
+    fn from_path(path: &str) -> Option<Self> {
+        match path {
+            "chengjiao" => Some(ListingKind::Chengjiao),
+            "ershoufang" => Some(ListingKind::Ershoufang),
+            "xiaoqu" => Some(ListingKind::Xiaoqu),
+            "zufang" => Some(ListingKind::Zufang),
+            "loupan" => Some(ListingKind::Loupan),
+            _ => None,
+        }
+    }
+}
+
+/// 搜索路径中页码之后的筛选段
+///
+/// 贝壳网把筛选条件编码成"字母前缀+数字"并拼接在页码后面，
+/// 例如 `/chengjiao/dazhulin/pg2l3de1/` 表示第2页、三室、精装。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum FilterSegment {
+    /// de1 精装 / de2 简装 / de3 毛坯
+    Decoration(u32),
+    /// l1..l6 居室数
+    Layout(u32),
+    /// a1..a8 面积档位
+    AreaBand(u32),
+    /// p1..p8 总价档位
+    PriceBand(u32),
+    /// f1..f5 朝向
+    Orientation(u32),
+    /// y1..y5 楼龄
+    BuildingAge(u32),
+    /// ie1 无电梯 / ie2 有电梯
+    Elevator(u32),
+    /// lc1..lc3 楼层
+    FloorLevel(u32),
+    /// ba{min}ea{max} 自定义面积区间（平米）
+    AreaRange { min: Option<u32>, max: Option<u32> },
+    /// bp{min}ep{max} 自定义总价区间（万）
+    PriceRange { min: Option<u32>, max: Option<u32> },
+    /// 尚未建模的筛选段，原样保留
+    Other { prefix: String, value: u32 },
+}
+
+impl FilterSegment {
+    /// 把形如 `l3ba80ea100de1` 的路径段拆成筛选段列表
+    pub fn parse_all(segment: &str) -> Result<Vec<FilterSegment>> {
+        let tokens = tokenize(segment)?;
+        let mut segments = Vec::new();
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let (prefix, value) = &tokens[i];
+            let next = tokens.get(i + 1);
+            let (segment, consumed) = match prefix.as_str() {
+                "de" => (FilterSegment::Decoration(*value), 1),
+                "l" => (FilterSegment::Layout(*value), 1),
+                "a" => (FilterSegment::AreaBand(*value), 1),
+                "p" => (FilterSegment::PriceBand(*value), 1),
+                "f" => (FilterSegment::Orientation(*value), 1),
+                "y" => (FilterSegment::BuildingAge(*value), 1),
+                "ie" => (FilterSegment::Elevator(*value), 1),
+                "lc" => (FilterSegment::FloorLevel(*value), 1),
+                "ba" => match next {
+                    Some((p, max)) if p == "ea" => (FilterSegment::AreaRange { min: Some(*value), max: Some(*max) }, 2),
+                    _ => (FilterSegment::AreaRange { min: Some(*value), max: None }, 1),
+                },
+                "ea" => (FilterSegment::AreaRange { min: None, max: Some(*value) }, 1),
+                "bp" => match next {
+                    Some((p, max)) if p == "ep" => (FilterSegment::PriceRange { min: Some(*value), max: Some(*max) }, 2),
+                    _ => (FilterSegment::PriceRange { min: Some(*value), max: None }, 1),
+                },
+                "ep" => (FilterSegment::PriceRange { min: None, max: Some(*value) }, 1),
+                _ => (FilterSegment::Other { prefix: prefix.clone(), value: *value }, 1),
+            };
+            segments.push(segment);
+            i += consumed;
+        }
+
+        Ok(segments)
+    }
+}
+
+impl fmt::Display for FilterSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterSegment::Decoration(v) => write!(f, "de{}", v),
+            FilterSegment::Layout(v) => write!(f, "l{}", v),
+            FilterSegment::AreaBand(v) => write!(f, "a{}", v),
+            FilterSegment::PriceBand(v) => write!(f, "p{}", v),
+            FilterSegment::Orientation(v) => write!(f, "f{}", v),
+            FilterSegment::BuildingAge(v) => write!(f, "y{}", v),
+            FilterSegment::Elevator(v) => write!(f, "ie{}", v),
+            FilterSegment::FloorLevel(v) => write!(f, "lc{}", v),
+            FilterSegment::AreaRange { min, max } => write_range(f, "ba", "ea", *min, *max),
+            FilterSegment::PriceRange { min, max } => write_range(f, "bp", "ep", *min, *max),
+            FilterSegment::Other { prefix, value } => write!(f, "{}{}", prefix, value),
+        }
+    }
+}
+
+impl FromStr for FilterSegment {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut segments = FilterSegment::parse_all(s)?;
+        if segments.len() != 1 {
+            return Err(anyhow::anyhow!("筛选段必须恰好包含一个条件: {}", s));
+        }
+        Ok(segments.remove(0))
+    }
+}
+
+impl TryFrom<String> for FilterSegment {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<FilterSegment> for String {
+    fn from(segment: FilterSegment) -> Self {
+        segment.to_string()
+    }
+}
+
+/// 贝壳网列表页地址：`https://{city}.ke.com/{kind}/{area}/pg{page}{filters}/`，租房为 `https://{city}.zu.ke.com/zufang/...`，新房为 `https://{city}.fang.ke.com/loupan/...`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchUrl {
+    /// 城市子域名，从镜像或本地模拟服务器的地址解析时为 None
+    pub city: Option<String>,
+    pub kind: ListingKind,
+    pub area: Option<String>,
+    pub filters: Vec<FilterSegment>,
+    /// 替代 `https://{city}.ke.com` 的站点地址，如本地模拟服务器
+    pub base_url: Option<String>,
+}
+
+impl SearchUrl {
+    pub fn new(city: &str, kind: ListingKind) -> Self {
+        Self {
+            city: Some(city.to_string()),
+            kind,
+            area: None,
+            filters: Vec::new(),
+            base_url: None,
+        }
+    }
+
+    /// 限定区域或商圈，如 dazhulin
+    pub fn area(mut self, slug: &str) -> Self {
+        self.area = Some(slug.to_string());
+        self
+    }
+
+    pub fn filters(mut self, segments: impl IntoIterator<Item = FilterSegment>) -> Self {
+        self.filters.extend(segments);
+        self
+    }
+
+    /// 改为向 `base_url` 发请求，如 `http://127.0.0.1:8080`，路径规则不变
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// 贝壳网上的站点，没有城市时为不带城市的 `ke.com` 等
+    pub fn host(&self) -> String {
+        match &self.city {
+            Some(city) => format!("{}.{}", city, self.kind.domain()),
+            None => self.kind.domain().to_string(),
+        }
+    }
+
+    pub fn origin(&self) -> String {
+        match &self.base_url {
+            Some(base_url) => base_url.clone(),
+            None => format!("https://{}", self.host()),
+        }
+    }
+
+    /// 第 `page` 页的完整地址
+    pub fn page_url(&self, page: u32) -> String {
+        let mut url = format!("{}/{}/", self.origin(), self.kind.path());
+        if let Some(area) = &self.area {
+            url.push_str(area);
+            url.push('/');
+        }
+        url.push_str(&format!("pg{}", page));
+        for segment in &self.filters {
+            url.push_str(&segment.to_string());
+        }
+        url.push('/');
+        url
+    }
+
+    /// 从已有的列表页地址还原出城市、区域和筛选条件，页码会被丢弃
+    ///
+    /// 列表类型、区域和筛选条件只看路径。`*.ke.com` 的地址从子域名取城市；其他站点（镜像、本地模拟服务器）
+    /// 没有城市，站点地址记在 `base_url` 中。
+    ///
+    /// 详情页地址（如 `/ershoufang/106118000001.html`、`/xiaoqu/3611056497876/`、`/loupan/p_jktlcbkcvf/xiangqing/`）会返回错误，
+    /// 否则其中的编号会被当成区域。
+    pub fn parse(url: &str) -> Result<Self> {
+        let parsed = reqwest::Url::parse(url)?;
+        let host = parsed.host_str().ok_or_else(|| anyhow::anyhow!("URL缺少主机名: {}", url))?;
+        // 租房站 cq.zu.ke.com、新房站 cq.fang.ke.com 的城市同样是第一级子域名
+        let city = host
+            .strip_suffix(".ke.com")
+            .and_then(|subdomain| subdomain.split('.').next())
+            .map(str::to_string);
+        let base_url = match city {
+            Some(_) => None,
+            None => Some(parsed.origin().ascii_serialization()),
+        };
+
+        let mut parts = parsed.path_segments().into_iter().flatten().filter(|p| !p.is_empty());
+        let kind = parts
+            .next()
+            .and_then(ListingKind::from_path)
+            .ok_or_else(|| anyhow::anyhow!("无法识别的列表频道: {}", url))?;
+
+        let parts: Vec<&str> = parts.collect();
+        if parts.iter().any(|part| is_detail_segment(kind, part)) {
+            return Err(anyhow::anyhow!("是详情页而不是列表页: {}", url));
+        }
+
+        // 页码和筛选段总在最后一段，之前的段都是区域，即使区域标识碰巧像筛选段（如 de1）
+        let last = parts.len().saturating_sub(1);
+        let mut search = SearchUrl { city, kind, area: None, filters: Vec::new(), base_url };
+        for (i, part) in parts.into_iter().enumerate() {
+            if i == last && is_filter_segment(part) {
+                let segments = FilterSegment::parse_all(part)?;
+                search.filters.extend(segments.into_iter().filter(|s| {
+                    !matches!(s, FilterSegment::Other { prefix, .. } if prefix == "pg")
+                }));
+            } else {
+                search.area = Some(part.to_string());
+            }
+        }
+
+        Ok(search)
+    }
+}
+
+impl fmt::Display for SearchUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.page_url(1))
+    }
+}
+
+/// 把页面上的房源链接换到 `base_url` 指向的站点，路径和查询参数不变
+///
+/// 列表页里的链接总是 `https://{city}.ke.com/...`，爬取镜像或本地模拟服务器时需要换掉站点部分。
+pub fn rebase_url(url: &str, base_url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => {
+            let mut rebased = format!("{}{}", base_url.trim_end_matches('/'), parsed.path());
+            if let Some(query) = parsed.query() {
+                rebased.push('?');
+                rebased.push_str(query);
+            }
+            rebased
+        }
+        // 相对链接直接拼在站点后面
+        Err(_) => format!("{}/{}", base_url.trim_end_matches('/'), url.trim_start_matches('/')),
+    }
+}
+
+fn write_range(f: &mut fmt::Formatter<'_>, min_prefix: &str, max_prefix: &str, min: Option<u32>, max: Option<u32>) -> fmt::Result {
+    if let Some(min) = min {
+        write!(f, "{}{}", min_prefix, min)?;
+    }
+    if let Some(max) = max {
+        write!(f, "{}{}", max_prefix, max)?;
+    }
+    Ok(())
+}
+
+/// 详情页路径中的段：`{id}.html`、楼盘的 `xiangqing` 和 `p_{id}`、小区的纯数字编号
+fn is_detail_segment(kind: ListingKind, part: &str) -> bool {
+    part.ends_with(".html")
+        || part == "xiangqing"
+        || (kind == ListingKind::Loupan && part.starts_with("p_"))
+        || (kind == ListingKind::Xiaoqu && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// 区域标识只含字母，而筛选段总是以数字结尾
+fn is_filter_segment(part: &str) -> bool {
+    part.ends_with(|c: char| c.is_ascii_digit()) && tokenize(part).is_ok()
+}
+
+fn tokenize(segment: &str) -> Result<Vec<(String, u32)>> {
+    let mut tokens = Vec::new();
+    let mut chars = segment.chars().peekable();
+
+    while chars.peek().is_some() {
+        let mut prefix = String::new();
+        while let Some(c) = chars.peek().filter(|c| c.is_ascii_lowercase()) {
+            prefix.push(*c);
+            chars.next();
+        }
+
+        let mut digits = String::new();
+        while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+            digits.push(*c);
+            chars.next();
+        }
+
+        if prefix.is_empty() || digits.is_empty() {
+            return Err(anyhow::anyhow!("无法解析的筛选段: {}", segment));
+        }
+        tokens.push((prefix, digits.parse()?));
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_splits_prefixes_and_values() {
+        let tokens = tokenize("de1l3a3p4").unwrap();
+        assert_eq!(
+            tokens,
+            [("de".to_string(), 1), ("l".to_string(), 3), ("a".to_string(), 3), ("p".to_string(), 4)]
+        );
+        assert_eq!(
+            FilterSegment::parse_all("de1l3a3p4").unwrap(),
+            [
+                FilterSegment::Decoration(1),
+                FilterSegment::Layout(3),
+                FilterSegment::AreaBand(3),
+                FilterSegment::PriceBand(4),
+            ]
+        );
+        assert!(tokenize("de").is_err());
+        assert!(tokenize("3l").is_err());
+    }
+
+    #[test]
+    fn page_url_keeps_area_containing_page_or_filter_text() {
+        let search = SearchUrl::new("cq", ListingKind::Ershoufang)
+            .area("pg1de1ang")
+            .filters([FilterSegment::Layout(3), FilterSegment::Decoration(1)]);
+        assert_eq!(search.page_url(1), "https://cq.ke.com/ershoufang/pg1de1ang/pg1l3de1/");
+        assert_eq!(search.page_url(12), "https://cq.ke.com/ershoufang/pg1de1ang/pg12l3de1/");
+
+        let parsed = SearchUrl::parse(&search.page_url(12)).unwrap();
+        assert_eq!(parsed, search);
+    }
+
+    #[test]
+    fn parse_treats_segments_before_the_last_as_area() {
+        let parsed = SearchUrl::parse("https://cq.ke.com/chengjiao/de1/pg2l3/").unwrap();
+        assert_eq!(parsed.area.as_deref(), Some("de1"));
+        assert_eq!(parsed.filters, [FilterSegment::Layout(3)]);
+
+        // 只有一段时按内容判断：以数字结尾的是页码和筛选段，否则是区域
+        let parsed = SearchUrl::parse("https://cq.ke.com/chengjiao/pg1de1/").unwrap();
+        assert_eq!(parsed.area, None);
+        assert_eq!(parsed.filters, [FilterSegment::Decoration(1)]);
+        let parsed = SearchUrl::parse("https://cq.ke.com/chengjiao/pg1ang/").unwrap();
+        assert_eq!(parsed.area.as_deref(), Some("pg1ang"));
+        assert!(parsed.filters.is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_detail_pages() {
+        assert!(SearchUrl::parse("https://cq.ke.com/ershoufang/106118000001.html").is_err());
+        assert!(SearchUrl::parse("https://cq.ke.com/xiaoqu/3611056497876/").is_err());
+        assert!(SearchUrl::parse("https://cq.fang.ke.com/loupan/p_jktlcbkcvf/xiangqing/").is_err());
+        assert!(SearchUrl::parse("http://127.0.0.1:8080/ershoufang/106118000001.html").is_err());
+    }
+
+    #[test]
+    fn parse_keeps_mirror_host_as_base_url() {
+        let parsed = SearchUrl::parse("http://127.0.0.1:8080/ershoufang/dazhulin/pg2l3de1/").unwrap();
+        assert_eq!(parsed.city, None);
+        assert_eq!(parsed.kind, ListingKind::Ershoufang);
+        assert_eq!(parsed.area.as_deref(), Some("dazhulin"));
+        assert_eq!(parsed.filters, [FilterSegment::Layout(3), FilterSegment::Decoration(1)]);
+        assert_eq!(parsed.page_url(3), "http://127.0.0.1:8080/ershoufang/dazhulin/pg3l3de1/");
+
+        let parsed = SearchUrl::parse("https://cq.zu.ke.com/zufang/dazhulin/pg1/").unwrap();
+        assert_eq!(parsed.city.as_deref(), Some("cq"));
+        assert_eq!(parsed.base_url, None);
+        assert!(SearchUrl::parse("http://127.0.0.1:8080/about/").is_err());
+    }
+}
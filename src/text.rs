@@ -0,0 +1,101 @@
+//! 从列表页文本中提取数字、日期等字段
+//!
+//! 页面上的文本格式并不统一（千分位逗号、单位、前后缀文字），这里的函数只负责取出数值，
+//! 取不到时返回 None，原始文本由调用方保留。
+
+use chrono::NaiveDate;
+
+/// 取出文本中的第一个数字，忽略千分位逗号，如 "13,408元/平" -> 13408.0、"挂牌125万" -> 125.0
+pub fn parse_number(text: &str) -> Option<f64> {
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() || (c == '.' && !number.is_empty() && !number.contains('.')) {
+            number.push(c);
+        } else if c == ',' && !number.is_empty() {
+            continue;
+        } else if !number.is_empty() {
+            break;
+        }
+    }
+    number.trim_end_matches('.').parse().ok()
+}
+
+/// 取出文本中的第一个整数，如 "成交周期46天" -> 46，"1,260户" -> 1260
+///
+/// 只有后面恰好跟着三位数字的逗号才当作千位分隔符，"2,3室" 这样的列表只取第一个数 2。
+pub fn parse_integer(text: &str) -> Option<u32> {
+    let chars: Vec<char> = text.chars().collect();
+    let start = chars.iter().position(|c| c.is_ascii_digit())?;
+    let mut digits = String::new();
+    for (i, c) in chars.iter().enumerate().skip(start) {
+        if c.is_ascii_digit() {
+            digits.push(*c);
+        } else if !(*c == ',' && is_thousands_group(&chars[i + 1..])) {
+            break;
+        }
+    }
+    digits.parse().ok()
+}
+
+/// 逗号之后是否恰好是一组三位数字
+fn is_thousands_group(rest: &[char]) -> bool {
+    rest.len() >= 3
+        && rest[..3].iter().all(|c| c.is_ascii_digit())
+        && !rest.get(3).is_some_and(|c| c.is_ascii_digit())
+}
+
+/// 解析成交日期，支持 "2024.05.12"、"2024-05-12"、"2024/05/12"、"2024年05月12日"
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    let text = text.trim();
+    ["%Y.%m.%d", "%Y-%m-%d", "%Y/%m/%d", "%Y年%m月%d日"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+}
+
+/// 把 "3天以前发布"、"1个月以前发布"、"2年以前发布" 换算成天数，月按30天、年按365天计
+pub fn parse_age_days(text: &str) -> Option<u32> {
+    if text.contains("今天") || text.contains("刚刚") {
+        return Some(0);
+    }
+    let count = parse_integer(text)?;
+    let unit_pos = text.find(|c: char| c.is_ascii_digit())?;
+    let rest = text[unit_pos..].trim_start_matches(|c: char| c.is_ascii_digit()).trim_start();
+    if rest.starts_with('天') {
+        Some(count)
+    } else if rest.starts_with("个月") || rest.starts_with('月') {
+        Some(count * 30)
+    } else if rest.starts_with('年') {
+        Some(count * 365)
+    } else {
+        None
+    }
+}
+
+/// 把可能缺失的值转换成展示文本，缺失时显示"未知"
+pub fn or_unknown<T: std::fmt::Display>(value: Option<T>, unit: &str) -> String {
+    match value {
+        Some(value) => format!("{}{}", value, unit),
+        None => "未知".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_integer_reads_thousands_separators() {
+        assert_eq!(parse_integer("1,260户"), Some(1260));
+        assert_eq!(parse_integer("规划户数 1,234,567 户"), Some(1234567));
+        assert_eq!(parse_integer("成交周期46天"), Some(46));
+    }
+
+    #[test]
+    fn parse_integer_stops_at_list_commas() {
+        assert_eq!(parse_integer("2,3室"), Some(2));
+        assert_eq!(parse_integer("1,2层"), Some(1));
+        assert_eq!(parse_integer("12,3456"), Some(12));
+        assert_eq!(parse_integer("共5层"), Some(5));
+        assert_eq!(parse_integer("暂无数据"), None);
+    }
+}
@@ -0,0 +1,218 @@
+use anyhow::Result;
+use scraper::{ElementRef, Html, Selector};
+use crate::checkpoint::Checkpoint;
+use crate::crawl::{crawl, CrawlReport, ListPage, ListScraper};
+use crate::error::ScrapeError;
+use crate::fetch::Fetcher;
+use crate::house::parse_build_year;
+use crate::pagination::PageInfo;
+use crate::region::{city_of, Area, Region, RegionFilter};
+use crate::retry::RetryPolicy;
+use crate::search_url::SearchUrl;
+use crate::text::{parse_integer, parse_number};
+use serde::{Deserialize, Serialize};
+
+/// 页面上出现的建筑类型，一个小区可能同时有多种
+const BUILDING_TYPES: [&str; 5] = ["板塔结合", "板楼", "塔楼", "平房", "别墅"];
+
+/// 小区列表页上的一个小区
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XiaoquData {
+    /// 小区编号，即 /xiaoqu/{id}/ 中的数字，与在售记录的 `community_id` 一致
+    pub id: Option<String>,
+    pub name: String,
+    /// 小区详情页链接
+    pub url: String,
+    pub region: Region,
+    /// 参考均价（元/平米）
+    pub average_price: Option<f64>,
+    pub build_year: Option<u32>,
+    /// 建筑类型，多种时用 "/" 连接，如 "板楼/塔楼"
+    pub building_type: String,
+    /// 在售二手房套数
+    pub on_sale_count: Option<u32>,
+    /// 近90天成交套数
+    pub sold_count: Option<u32>,
+    /// 正在出租套数
+    pub renting_count: Option<u32>,
+    /// 页面上的原始文本，用于核对解析结果
+    pub raw: XiaoquRaw,
+}
+
+/// 小区各字段在页面上的原始文本，没有找到时为空
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XiaoquRaw {
+    pub average_price: String,
+    /// 均价说明，如 "5月二手房参考均价"
+    pub price_desc: String,
+    /// 成交和出租套数，如 "90天成交12套 | 31套正在出租"
+    pub house_info: String,
+    /// 区域、建筑类型和建成年份，如 "渝北 大竹林 / 板楼/塔楼 / 2015年建成"
+    pub position_info: String,
+    pub on_sale_count: String,
+}
+
+pub struct XiaoquScraper {
+    fetcher: Fetcher,
+    retry_policy: RetryPolicy,
+    concurrency: usize,
+}
+
+impl XiaoquScraper {
+    pub fn new(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher,
+            retry_policy: RetryPolicy::default(),
+            concurrency: 1,
+        }
+    }
+
+    /// 设置单页失败时的重试策略
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// 设置同时在途的列表页请求数，实际请求速率仍受 Fetcher 的限速约束
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub async fn scrape_xiaoqu_page(&self, url: &str) -> Result<ListPage<XiaoquData>, ScrapeError> {
+        let html_content = self.fetcher.fetch(url).await?;
+        self.parse_page(&html_content).non_empty()
+    }
+
+    /// 解析小区列表页 HTML，不发起任何网络请求
+    pub fn parse_page(&self, html_content: &str) -> ListPage<XiaoquData> {
+        let document = Html::parse_document(html_content);
+
+        let list_selector = Selector::parse(".listContent li.xiaoquListItem").unwrap();
+        let regions = RegionFilter::parse(&document, "xiaoqu");
+
+        let mut results: Vec<XiaoquData> = Vec::new();
+        for item in document.select(&list_selector) {
+            match self.parse_list_item(&item, &regions) {
+                Ok(xiaoqu_data) => results.push(xiaoqu_data),
+                Err(e) => println!("跳过列表项: {}", e),
+            }
+        }
+
+        println!("成功解析 {} 条小区数据", results.len());
+        ListPage { items: results, info: PageInfo::parse(&document) }
+    }
+
+    fn parse_list_item(&self, item: &ElementRef, regions: &RegionFilter) -> Result<XiaoquData, ScrapeError> {
+        let title_selector = Selector::parse(".title a").unwrap();
+        let title = item.select(&title_selector).next().ok_or_else(|| ScrapeError::Parse {
+            field: "name",
+            reason: "列表项中没有 .title a".to_string(),
+        })?;
+        let name = title.text().collect::<String>().trim().to_string();
+        let url = title.value().attr("href").unwrap_or("").to_string();
+
+        let raw = XiaoquRaw {
+            average_price: select_text(item, ".totalPrice span"),
+            price_desc: select_text(item, ".priceDesc"),
+            house_info: select_text(item, ".houseInfo"),
+            position_info: select_text(item, ".positionInfo"),
+            on_sale_count: select_text(item, ".totalSellCount span"),
+        };
+
+        // 区县和商圈链接：<a class="district">渝北</a> <a class="bizcircle">大竹林</a>
+        let link = |class: &str| {
+            let selector = Selector::parse(&format!(".positionInfo a.{}", class)).unwrap();
+            item.select(&selector).next().and_then(|el| Area::from_link(&el))
+        };
+        let mut region = regions.locate(link("bizcircle"), city_of(&url));
+        if let Some(district) = link("district") {
+            region.district = Some(district);
+        }
+
+        let building_type = BUILDING_TYPES
+            .into_iter()
+            .filter(|kind| raw.position_info.contains(kind))
+            .collect::<Vec<_>>()
+            .join("/");
+
+        // "90天成交12套 | 31套正在出租"：成交套数在 "成交" 之后，前面的90是统计天数
+        let sold_count = raw.house_info
+            .find("成交")
+            .and_then(|pos| parse_integer(&raw.house_info[pos..]));
+        let renting_count = raw.house_info
+            .split('|')
+            .find(|part| part.contains("出租"))
+            .and_then(parse_integer);
+
+        Ok(XiaoquData {
+            id: community_id(&url).or_else(|| {
+                ["data-housecode", "data-id"]
+                    .iter()
+                    .find_map(|attr| item.value().attr(attr))
+                    .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+                    .map(str::to_string)
+            }),
+            name,
+            url,
+            region,
+            average_price: parse_number(&raw.average_price),
+            build_year: parse_build_year(&raw.position_info),
+            building_type: if building_type.is_empty() { "未知".to_string() } else { building_type },
+            on_sale_count: parse_integer(&raw.on_sale_count),
+            sold_count,
+            renting_count,
+            raw,
+        })
+    }
+
+    /// 爬取 `start_page` 到 `end_page` 的小区列表页，`end_page` 为 None 时根据第一页的分页信息自动确定页数
+    ///
+    /// 每页结果写入 `checkpoint`，已在断点中完成的页会被跳过。
+    pub async fn scrape_multiple_pages(&self, search: &SearchUrl, start_page: u32, end_page: Option<u32>, checkpoint: &mut Checkpoint) -> Result<CrawlReport<XiaoquData>> {
+        crawl(self, search, start_page, end_page, checkpoint).await
+    }
+}
+
+impl ListScraper for XiaoquScraper {
+    type Item = XiaoquData;
+
+    async fn scrape_page(&self, url: &str) -> Result<ListPage<XiaoquData>, ScrapeError> {
+        self.scrape_xiaoqu_page(url).await
+    }
+
+    fn item_key(item: &XiaoquData) -> String {
+        match &item.id {
+            Some(id) => id.clone(),
+            None if !item.url.is_empty() => item.url.clone(),
+            None => item.name.clone(),
+        }
+    }
+
+    fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+}
+
+/// 小区链接中的编号，如 `https://cq.ke.com/xiaoqu/3611056497876/` -> 3611056497876
+pub fn community_id(href: &str) -> Option<String> {
+    let path = href.split(['?', '#']).next()?;
+    let mut parts = path.split('/').filter(|part| !part.is_empty());
+    parts.find(|part| *part == "xiaoqu")?;
+    parts
+        .next()
+        .filter(|id| id.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+fn select_text(item: &ElementRef, selector: &str) -> String {
+    let selector = Selector::parse(selector).unwrap();
+    item.select(&selector)
+        .next()
+        .map(|el| el.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default()
+}
@@ -0,0 +1,141 @@
+use crate::detail::{canonical_url, check_id, known, DetailOptions, DetailRecord, DetailScraper};
+use crate::error::ScrapeError;
+use crate::fetch::Fetcher;
+use crate::house::parse_build_year;
+use crate::text::{parse_integer, parse_number};
+use crate::xiaoqu::{community_id, XiaoquData};
+use anyhow::Result;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 小区详情页上的信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XiaoquDetail {
+    /// 小区编号，取自页面的规范地址 `/xiaoqu/{id}/`
+    pub community_id: Option<String>,
+    /// 参考均价（元/平米）
+    pub average_price: Option<f64>,
+    pub build_year: Option<u32>,
+    /// 建筑类型，如 "板楼/塔楼"
+    pub building_type: Option<String>,
+    /// 楼栋总数
+    pub building_count: Option<u32>,
+    /// 房屋总数（户）
+    pub household_count: Option<u32>,
+    pub developer: Option<String>,
+    pub property_company: Option<String>,
+    /// 物业费下限（元/平米/月），只有一个值时上下限相同
+    pub property_fee_min: Option<f64>,
+    /// 物业费上限（元/平米/月）
+    pub property_fee_max: Option<f64>,
+    /// 小区信息栏中的全部条目
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// 小区列表记录与详情合并后的记录
+pub type XiaoquRecord = DetailRecord<XiaoquData, XiaoquDetail>;
+
+pub struct XiaoquDetailScraper {
+    fetcher: Fetcher,
+    options: DetailOptions,
+}
+
+impl XiaoquDetailScraper {
+    pub fn new(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher,
+            options: DetailOptions::default(),
+        }
+    }
+
+    pub async fn scrape_xiaoqu_detail(&self, url: &str) -> Result<XiaoquDetail, ScrapeError> {
+        let html_content = self.fetcher.fetch(url).await?;
+        let detail = self.parse_detail(&html_content);
+        if detail.attributes.is_empty() && detail.average_price.is_none() {
+            return Err(ScrapeError::EmptyPage);
+        }
+        Ok(detail)
+    }
+
+    /// 解析详情页 HTML，不发起任何网络请求
+    pub fn parse_detail(&self, html_content: &str) -> XiaoquDetail {
+        let document = Html::parse_document(html_content);
+
+        // 小区信息：<div class="xiaoquInfoItem"><span class="xiaoquInfoLabel">楼栋总数</span><span class="xiaoquInfoContent">28栋</span></div>
+        let item_selector = Selector::parse(".xiaoquInfo .xiaoquInfoItem").unwrap();
+        let label_selector = Selector::parse(".xiaoquInfoLabel").unwrap();
+        let content_selector = Selector::parse(".xiaoquInfoContent").unwrap();
+        let attributes: BTreeMap<String, String> = document.select(&item_selector)
+            .filter_map(|item| {
+                let text = |selector: &Selector| {
+                    let text = item.select(selector).next()?.text().collect::<String>();
+                    Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+                };
+                Some((text(&label_selector)?, text(&content_selector)?))
+            })
+            .filter(|(name, _)| !name.is_empty())
+            .collect();
+        let attribute = |name: &str| known(attributes.get(name));
+
+        let price_selector = Selector::parse(".xiaoquUnitPrice").unwrap();
+        let average_price = document.select(&price_selector)
+            .next()
+            .and_then(|el| parse_number(&el.text().collect::<String>()));
+
+        let (property_fee_min, property_fee_max) = attribute("物业费用")
+            .map(|fee| parse_fee_range(&fee))
+            .unwrap_or_default();
+
+        XiaoquDetail {
+            community_id: canonical_url(&document).and_then(|url| community_id(&url)),
+            average_price,
+            build_year: attribute("建筑年代").and_then(|text| parse_build_year(&text)),
+            building_type: attribute("建筑类型"),
+            building_count: attribute("楼栋总数").and_then(|text| parse_integer(&text)),
+            household_count: attribute("房屋总数").and_then(|text| parse_integer(&text)),
+            developer: attribute("开发商"),
+            property_company: attribute("物业公司"),
+            property_fee_min,
+            property_fee_max,
+            attributes,
+        }
+    }
+}
+
+impl DetailScraper for XiaoquDetailScraper {
+    type Summary = XiaoquData;
+    type Detail = XiaoquDetail;
+
+    fn options(&self) -> &DetailOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut DetailOptions {
+        &mut self.options
+    }
+
+    fn summary_url(summary: &XiaoquData) -> &str {
+        &summary.url
+    }
+
+    async fn scrape_detail(&self, url: &str) -> Result<XiaoquDetail, ScrapeError> {
+        self.scrape_xiaoqu_detail(url).await
+    }
+
+    /// 详情页上的小区编号与列表记录不一致时（如被重定向到了其他小区）不合并
+    fn check_detail(&self, summary: &XiaoquData, detail: &XiaoquDetail) -> Result<(), ScrapeError> {
+        check_id("community_id", "小区编号", summary.id.as_ref(), detail.community_id.as_ref())
+    }
+}
+
+/// 物业费 "1.8至2.5元/平米/月" -> (1.8, 2.5)，"2元/平米/月" -> (2.0, 2.0)
+fn parse_fee_range(text: &str) -> (Option<f64>, Option<f64>) {
+    match text.split_once(['至', '-', '~']) {
+        Some((min, max)) => (parse_number(min), parse_number(max)),
+        None => {
+            let fee = parse_number(text);
+            (fee, fee)
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use anyhow::Result;
+use scraper::{ElementRef, Html, Selector};
+use crate::checkpoint::Checkpoint;
+use crate::crawl::{crawl, CrawlReport, ListPage, ListScraper};
+use crate::error::ScrapeError;
+use crate::fetch::Fetcher;
+use crate::house::Floor;
+use crate::listing::ListingId;
+use crate::pagination::PageInfo;
+use crate::region::{city_of, Area, Region, RegionFilter};
+use crate::retry::RetryPolicy;
+use crate::search_url::SearchUrl;
+use crate::text::{parse_age_days, parse_number};
+use chrono::{Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// 出租方式，取自标题前缀 "整租·"、"合租·"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RentalType {
+    #[serde(rename = "整租")]
+    Whole,
+    #[serde(rename = "合租")]
+    Shared,
+}
+
+impl RentalType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RentalType::Whole => "整租",
+            RentalType::Shared => "合租",
+        }
+    }
+
+    fn from_title(title: &str) -> Option<Self> {
+        if title.starts_with("整租") {
+            Some(RentalType::Whole)
+        } else if title.starts_with("合租") {
+            Some(RentalType::Shared)
+        } else {
+            None
+        }
+    }
+}
+
+/// 租房列表页上的一套出租房源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZufangData {
+    /// 房源编号，如 CQ1234567890，优先取列表项的 data-house_code，公寓的详情链接中是公寓编号而不是房源编号
+    pub id: Option<ListingId>,
+    pub title: String,
+    /// 详情链接，列表页上是相对地址，爬取时按列表页地址补全
+    pub url: String,
+    /// 整租或合租，公寓等没有前缀的房源为 None
+    pub rental_type: Option<RentalType>,
+    /// 月租金（元/月），公寓显示区间时取下限
+    pub rent: Option<f64>,
+    /// 面积（平方米），合租时是所租房间的面积
+    pub area: Option<f64>,
+    pub layout: String,
+    pub orientation: String,
+    pub floor: Floor,
+    pub community: String,
+    /// 小区编号，取自小区链接 /zufang/c{id}/，与小区表的编号一致
+    pub community_id: Option<String>,
+    /// 所在城市、区县和商圈，取自列表项的区域链接和列表页的区域筛选栏
+    pub region: Region,
+    pub tags: Vec<String>,
+    /// 由 "N天前维护" 和爬取日期推算的最近维护日期，租房列表上没有发布时间，只能近似当作上架日期
+    pub listed_on: Option<NaiveDate>,
+    /// 页面上的原始文本，用于核对解析结果
+    pub raw: ZufangRaw,
+}
+
+impl ZufangData {
+    /// 每平米月租金（元/平米/月）
+    pub fn rent_per_square_meter(&self) -> Option<f64> {
+        match (self.rent, self.area) {
+            (Some(rent), Some(area)) if area > 0.0 => Some(rent / area),
+            _ => None,
+        }
+    }
+}
+
+/// 出租房源各字段在页面上的原始文本，没有找到时为空
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ZufangRaw {
+    pub rent: String,
+    /// 区域、面积、朝向、户型和楼层，如 "渝北-大竹林-金科天籁城 / 89.5㎡ / 南 北 / 3室2厅2卫 / 中楼层 （18层）"
+    pub description: String,
+    pub area: String,
+    pub floor: String,
+    pub maintain_time: String,
+}
+
+pub struct ZufangScraper {
+    fetcher: Fetcher,
+    retry_policy: RetryPolicy,
+    concurrency: usize,
+}
+
+impl ZufangScraper {
+    pub fn new(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher,
+            retry_policy: RetryPolicy::default(),
+            concurrency: 1,
+        }
+    }
+
+    /// 设置单页失败时的重试策略
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// 设置同时在途的列表页请求数，实际请求速率仍受 Fetcher 的限速约束
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub async fn scrape_zufang_page(&self, url: &str) -> Result<ListPage<ZufangData>, ScrapeError> {
+        let html_content = self.fetcher.fetch(url).await?;
+        self.parse_page_at(&html_content, url, Local::now().date_naive()).non_empty()
+    }
+
+    /// 解析租房列表页 HTML，不发起任何网络请求，维护日期按今天推算，链接保持页面上的相对地址
+    pub fn parse_page(&self, html_content: &str) -> ListPage<ZufangData> {
+        self.parse(html_content, None, Local::now().date_naive())
+    }
+
+    /// 解析 `crawled_on` 当天从 `page_url` 抓取的列表页 HTML，相对链接按 `page_url` 补全
+    pub fn parse_page_at(&self, html_content: &str, page_url: &str, crawled_on: NaiveDate) -> ListPage<ZufangData> {
+        self.parse(html_content, reqwest::Url::parse(page_url).ok(), crawled_on)
+    }
+
+    fn parse(&self, html_content: &str, page_url: Option<reqwest::Url>, crawled_on: NaiveDate) -> ListPage<ZufangData> {
+        let document = Html::parse_document(html_content);
+
+        let list_selector = Selector::parse(".content__list .content__list--item").unwrap();
+        let regions = RegionFilter::parse(&document, "zufang");
+
+        let mut results: Vec<ZufangData> = Vec::new();
+        for item in document.select(&list_selector) {
+            match self.parse_list_item(&item, page_url.as_ref(), crawled_on, &regions) {
+                Ok(zufang_data) => results.push(zufang_data),
+                Err(e) => println!("跳过列表项: {}", e),
+            }
+        }
+
+        println!("成功解析 {} 条租房数据", results.len());
+        ListPage { items: results, info: PageInfo::parse(&document) }
+    }
+
+    fn parse_list_item(&self, item: &ElementRef, page_url: Option<&reqwest::Url>, crawled_on: NaiveDate, regions: &RegionFilter) -> Result<ZufangData, ScrapeError> {
+        // 没有标题的是广告位
+        let title_selector = Selector::parse(".content__list--item--title a").unwrap();
+        let title_link = item.select(&title_selector).next().ok_or_else(|| ScrapeError::Parse {
+            field: "title",
+            reason: "列表项中没有 .content__list--item--title a".to_string(),
+        })?;
+        let title = title_link.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+        let href = title_link.value().attr("href").unwrap_or("");
+        let url = match page_url.and_then(|base| base.join(href).ok()) {
+            Some(url) if !href.is_empty() => url.to_string(),
+            _ => href.to_string(),
+        };
+
+        // 描述：<a>渝北</a>-<a>大竹林</a>-<a>金科天籁城</a> <i>/</i> 89.5㎡ <i>/</i> 南 北 <i>/</i> 3室2厅2卫 <i>/</i> 中楼层 （18层）
+        let des_selector = Selector::parse(".content__list--item--des").unwrap();
+        let des = item.select(&des_selector).next();
+        let description = des
+            .map(|el| el.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" "))
+            .unwrap_or_default();
+
+        let mut area = String::new();
+        let mut floor = String::new();
+        let mut layout = "未知".to_string();
+        let mut orientation = "未知".to_string();
+        for part in description.split('/').skip(1).map(str::trim) {
+            if part.contains('㎡') || part.contains("平米") {
+                area = part.to_string();
+            } else if part.contains('室') {
+                layout = part.to_string();
+            } else if part.contains('层') {
+                floor = part.to_string();
+            } else if !part.is_empty() && part.chars().all(|c| "东南西北 ".contains(c)) {
+                orientation = part.to_string();
+            }
+        }
+
+        // 区域链接依次是区县、商圈和小区，小区链接为 /zufang/c{id}/
+        let link_selector = Selector::parse("a").unwrap();
+        let links: Vec<ElementRef> = des.map(|el| el.select(&link_selector).collect()).unwrap_or_default();
+        let areas: Vec<Area> = links.iter().filter_map(Area::from_link).collect();
+        let community_link = links.iter().find(|el| el.value().attr("href").and_then(community_id).is_some());
+        let community = community_link
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .unwrap_or_else(|| "未知".to_string());
+
+        let mut region = regions.locate(areas.get(1).cloned(), city_of(&url));
+        if let Some(district) = areas.first() {
+            region.district = Some(district.clone());
+        }
+
+        let tag_selector = Selector::parse(".content__list--item--bottom i").unwrap();
+        let tags: Vec<String> = item.select(&tag_selector)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+
+        let raw = ZufangRaw {
+            rent: select_text(item, ".content__list--item-price"),
+            description,
+            area,
+            floor,
+            maintain_time: select_text(item, ".content__list--item--time"),
+        };
+
+        Ok(ZufangData {
+            id: item.value().attr("data-house_code")
+                .and_then(ListingId::parse)
+                .or_else(|| ListingId::from_element(item)),
+            rental_type: RentalType::from_title(&title),
+            title,
+            url,
+            rent: parse_number(&raw.rent),
+            area: parse_number(&raw.area),
+            layout,
+            orientation,
+            floor: Floor::parse(&raw.floor),
+            community,
+            community_id: community_link.and_then(|el| el.value().attr("href")).and_then(community_id),
+            region,
+            tags,
+            listed_on: parse_age_days(&raw.maintain_time)
+                .map(|days| crawled_on - Duration::days(days as i64)),
+            raw,
+        })
+    }
+
+    /// 爬取 `start_page` 到 `end_page` 的租房列表页，`end_page` 为 None 时根据第一页的分页信息自动确定页数
+    ///
+    /// 每页结果写入 `checkpoint`，已在断点中完成的页会被跳过。
+    pub async fn scrape_multiple_pages(&self, search: &SearchUrl, start_page: u32, end_page: Option<u32>, checkpoint: &mut Checkpoint) -> Result<CrawlReport<ZufangData>> {
+        crawl(self, search, start_page, end_page, checkpoint).await
+    }
+}
+
+impl ListScraper for ZufangScraper {
+    type Item = ZufangData;
+
+    async fn scrape_page(&self, url: &str) -> Result<ListPage<ZufangData>, ScrapeError> {
+        self.scrape_zufang_page(url).await
+    }
+
+    fn item_key(item: &ZufangData) -> String {
+        match &item.id {
+            Some(id) => id.to_string(),
+            None if !item.url.is_empty() => item.url.clone(),
+            None => item.title.clone(),
+        }
+    }
+
+    fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+}
+
+/// 租房小区链接中的编号，如 `/zufang/c3611056497876/` -> 3611056497876
+pub fn community_id(href: &str) -> Option<String> {
+    let path = href.split(['?', '#']).next()?;
+    let mut parts = path.split('/').filter(|part| !part.is_empty());
+    parts.find(|part| *part == "zufang")?;
+    parts
+        .next()
+        .and_then(|part| part.strip_prefix('c'))
+        .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+fn select_text(item: &ElementRef, selector: &str) -> String {
+    let selector = Selector::parse(selector).unwrap();
+    item.select(&selector)
+        .next()
+        .map(|el| el.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default()
+}
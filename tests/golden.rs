@@ -0,0 +1,146 @@
+//! 列表项和详情页解析的黄金测试
+//!
+//! `tests/fixtures/golden/<类型>/` 下每个 `.html` 是一段保存下来的贝壳网列表页片段或详情页，
+//! 同名的 `.json` 是期望的解析结果。解析逻辑有意修改后，用下面的命令刷新期望结果并检查差异：
+//!
+//! ```bash
+//! UPDATE_GOLDENS=1 cargo test --test golden
+//! git diff tests/fixtures/golden
+//! ```
+
+use fast_to_mortgage_slave::chengjiao::ChengjiaoScraper;
+use fast_to_mortgage_slave::chengjiao_detail::ChengjiaoDetailScraper;
+use fast_to_mortgage_slave::ershoufang::ErshoufangScraper;
+use fast_to_mortgage_slave::ershoufang_detail::ErshoufangDetailScraper;
+use fast_to_mortgage_slave::fetch::Fetcher;
+use fast_to_mortgage_slave::loupan::LoupanScraper;
+use fast_to_mortgage_slave::loupan_detail::LoupanDetailScraper;
+use fast_to_mortgage_slave::xiaoqu::XiaoquScraper;
+use fast_to_mortgage_slave::xiaoqu_detail::XiaoquDetailScraper;
+use fast_to_mortgage_slave::zufang::ZufangScraper;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn golden_dir(kind: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/golden").join(kind)
+}
+
+fn update_goldens() -> bool {
+    std::env::var_os("UPDATE_GOLDENS").is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// 对 `kind` 目录下的每个片段运行 `parse`，与同名 `.json` 比较，列出所有不一致的片段
+fn check_goldens<T: Serialize>(kind: &str, parse: impl Fn(&str) -> Vec<T>) {
+    let dir = golden_dir(kind);
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("读取 {} 失败: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "{} 下没有任何片段", dir.display());
+
+    let mut failures = Vec::new();
+    for html_path in &fixtures {
+        let html = fs::read_to_string(html_path).unwrap();
+        let actual = serde_json::to_string_pretty(&parse(&html)).unwrap() + "\n";
+        let golden_path = html_path.with_extension("json");
+
+        if update_goldens() {
+            fs::write(&golden_path, &actual).unwrap();
+            continue;
+        }
+
+        match fs::read_to_string(&golden_path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(format!("{}:\n{}", golden_path.display(), diff(&expected, &actual))),
+            Err(_) => failures.push(format!("{}: 缺少期望结果", golden_path.display())),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "解析结果与期望不一致，确认是有意修改后用 UPDATE_GOLDENS=1 cargo test --test golden 刷新:\n\n{}",
+        failures.join("\n\n")
+    );
+}
+
+/// 逐行比较，只列出不同的行
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut lines = Vec::new();
+    for i in 0..expected.len().max(actual.len()) {
+        let (e, a) = (expected.get(i), actual.get(i));
+        if e != a {
+            if let Some(e) = e {
+                lines.push(format!("{:>4} - {}", i + 1, e));
+            }
+            if let Some(a) = a {
+                lines.push(format!("{:>4} + {}", i + 1, a));
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+#[test]
+fn chengjiao_list_items() {
+    let scraper = ChengjiaoScraper::new(Fetcher::new(String::new()));
+    check_goldens("chengjiao", |html| scraper.parse_page(html).items);
+}
+
+#[test]
+fn chengjiao_detail_pages() {
+    let scraper = ChengjiaoDetailScraper::new(Fetcher::new(String::new()));
+    check_goldens("chengjiao_detail", |html| vec![scraper.parse_detail(html)]);
+}
+
+#[test]
+fn ershoufang_list_items() {
+    let scraper = ErshoufangScraper::new(Fetcher::new(String::new()));
+    // 固定爬取日期，推算出的挂牌日期才不会随运行日期变化
+    let crawled_on = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+    check_goldens("ershoufang", |html| scraper.parse_page_on(html, crawled_on).items);
+}
+
+#[test]
+fn ershoufang_detail_pages() {
+    let scraper = ErshoufangDetailScraper::new(Fetcher::new(String::new()));
+    check_goldens("ershoufang_detail", |html| vec![scraper.parse_detail(html)]);
+}
+
+#[test]
+fn xiaoqu_list_items() {
+    let scraper = XiaoquScraper::new(Fetcher::new(String::new()));
+    check_goldens("xiaoqu", |html| scraper.parse_page(html).items);
+}
+
+#[test]
+fn xiaoqu_detail_pages() {
+    let scraper = XiaoquDetailScraper::new(Fetcher::new(String::new()));
+    check_goldens("xiaoqu_detail", |html| vec![scraper.parse_detail(html)]);
+}
+
+#[test]
+fn zufang_list_items() {
+    let scraper = ZufangScraper::new(Fetcher::new(String::new()));
+    let crawled_on = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+    check_goldens("zufang", |html| {
+        scraper.parse_page_at(html, "https://cq.zu.ke.com/zufang/dazhulin/", crawled_on).items
+    });
+}
+
+#[test]
+fn loupan_list_items() {
+    let scraper = LoupanScraper::new(Fetcher::new(String::new()));
+    check_goldens("loupan", |html| scraper.parse_page_at(html, "https://cq.fang.ke.com/loupan/dazhulin/").items);
+}
+
+#[test]
+fn loupan_detail_pages() {
+    let scraper = LoupanDetailScraper::new(Fetcher::new(String::new()));
+    check_goldens("loupan_detail", |html| vec![scraper.parse_detail(html)]);
+}
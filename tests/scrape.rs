@@ -0,0 +1,895 @@
+//! 通过本地模拟服务器端到端测试列表爬取，不访问真实的贝壳网
+
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use fast_to_mortgage_slave::archive::{Archive, ArchivedPage};
+use fast_to_mortgage_slave::checkpoint::Checkpoint;
+use fast_to_mortgage_slave::chengjiao::ChengjiaoScraper;
+use fast_to_mortgage_slave::chengjiao_detail::ChengjiaoDetailScraper;
+use fast_to_mortgage_slave::community::CommunityTable;
+use fast_to_mortgage_slave::detail::DetailScraper;
+use fast_to_mortgage_slave::error::ScrapeError;
+use fast_to_mortgage_slave::ershoufang::{ErshoufangData, ErshoufangScraper};
+use fast_to_mortgage_slave::ershoufang_detail::ErshoufangDetailScraper;
+use fast_to_mortgage_slave::fetch::Fetcher;
+use fast_to_mortgage_slave::house::FloorPosition;
+use fast_to_mortgage_slave::loupan::{LoupanScraper, SaleStatus};
+use fast_to_mortgage_slave::loupan_detail::LoupanDetailScraper;
+use fast_to_mortgage_slave::mock::{MockResponse, MockServer};
+use fast_to_mortgage_slave::output::OutputFormat;
+use fast_to_mortgage_slave::price_history::PriceHistory;
+use fast_to_mortgage_slave::ratelimit::RateLimit;
+use fast_to_mortgage_slave::reparse::reparse_archive;
+use fast_to_mortgage_slave::retry::{ErrorClass, RetryPolicy};
+use fast_to_mortgage_slave::search_url::{ListingKind, SearchUrl};
+use fast_to_mortgage_slave::xiaoqu::XiaoquScraper;
+use fast_to_mortgage_slave::xiaoqu_detail::XiaoquDetailScraper;
+use fast_to_mortgage_slave::zufang::{RentalType, ZufangScraper};
+use reqwest::StatusCode;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ERSHOUFANG_PG1: &str = "/ershoufang/dazhulin/pg1/";
+const ERSHOUFANG_PG2: &str = "/ershoufang/dazhulin/pg2/";
+
+fn fixture(name: &str) -> String {
+    read_fixture("list", name)
+}
+
+fn detail_fixture(name: &str) -> String {
+    read_fixture("detail", name)
+}
+
+fn read_fixture(dir: &str, name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(dir).join(name);
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("读取 {} 失败: {}", path.display(), e))
+}
+
+/// 测试用的临时目录，每个测试独立
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ke-scrape-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 不限速的 Fetcher，模拟服务器上没有必要等待
+fn fetcher() -> Fetcher {
+    Fetcher::new(String::new()).with_rate_limit(RateLimit {
+        requests_per_minute: 60_000,
+        burst: 100,
+        min_delay_ms: 0,
+        max_delay_ms: 0,
+    })
+}
+
+fn retry(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_delay_ms: 1,
+        throttled_delay_ms: 1,
+        max_delay_ms: 10,
+        jitter: 0.0,
+    }
+}
+
+fn ershoufang_search(server: &MockServer) -> SearchUrl {
+    SearchUrl::new("cq", ListingKind::Ershoufang)
+        .area("dazhulin")
+        .base_url(&server.base_url())
+}
+
+#[tokio::test]
+async fn ershoufang_crawl_follows_page_data() {
+    let server = MockServer::start().await.unwrap();
+    server.route(ERSHOUFANG_PG1, MockResponse::Html(fixture("ershoufang_pg1.html")));
+    server.route(ERSHOUFANG_PG2, MockResponse::Html(fixture("ershoufang_pg2.html")));
+
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(1));
+    let mut checkpoint = Checkpoint::open(&temp_dir("follow"), "ershoufang", false).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    let titles: Vec<&str> = report.items.iter().map(|item| item.title.as_str()).collect();
+    assert_eq!(titles, ["金科天籁城 精装三房 南北通透", "龙湖春森彼岸 两房 低总价", "中铁任之健康城 四房 带露台"]);
+    assert!(report.failed_pages.is_empty());
+    let first = &report.items[0];
+    assert_eq!(first.total_price, Some(120.0));
+    assert_eq!(first.area, Some(89.5));
+    assert_eq!(first.floor.position, Some(FloorPosition::Middle));
+    assert_eq!(first.floor.total_floors, Some(18));
+    assert_eq!(first.region.describe(), "渝北/大竹林");
+    // page-data 中只有2页，不应该请求第3页
+    assert_eq!(server.requests(), [ERSHOUFANG_PG1, ERSHOUFANG_PG2]);
+}
+
+#[tokio::test]
+async fn listings_are_deduplicated_by_id() {
+    let server = MockServer::start().await.unwrap();
+    server.route(ERSHOUFANG_PG1, MockResponse::Html(fixture("ershoufang_pg1.html")));
+    // 翻页期间第1页的房源被刷新到了第2页，链接带上了跟踪参数，标题也改了
+    let pg2 = fixture("ershoufang_pg2.html")
+        .replace("106118000003.html", "106118000001.html?fb_expo_id=123")
+        .replace("中铁任之健康城 四房 带露台", "金科天籁城 精装三房 急售");
+    server.route(ERSHOUFANG_PG2, MockResponse::Html(pg2));
+
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(1));
+    let mut checkpoint = Checkpoint::open(&temp_dir("dedup"), "ershoufang", false).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = report.items.iter().map(|item| item.id.as_ref().unwrap().as_str()).collect();
+    assert_eq!(ids, ["106118000001", "106118000002"]);
+    assert_eq!(report.items[0].title, "金科天籁城 精装三房 南北通透");
+}
+
+#[tokio::test]
+async fn concurrent_pages_keep_page_order() {
+    let server = MockServer::start().await.unwrap();
+    let pg1 = fixture("ershoufang_pg1.html").replace(r#""totalPage":2"#, r#""totalPage":4"#);
+    server.route(ERSHOUFANG_PG1, MockResponse::Html(pg1));
+    // 第2页先失败一次，完成得比第3、4页晚
+    server.route_sequence(
+        ERSHOUFANG_PG2,
+        vec![MockResponse::Status(503), MockResponse::Html(fixture("ershoufang_pg2.html"))],
+    );
+    for page in [3, 4] {
+        let html = fixture("ershoufang_pg2.html").replace("106118000003", &format!("10611800000{}", page + 1));
+        server.route(&format!("/ershoufang/dazhulin/pg{}/", page), MockResponse::Html(html));
+    }
+
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(3)).with_concurrency(3);
+    let mut checkpoint = Checkpoint::open(&temp_dir("concurrent"), "ershoufang", false).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    let urls: Vec<&str> = report.items.iter().map(|item| item.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://cq.ke.com/ershoufang/106118000001.html",
+            "https://cq.ke.com/ershoufang/106118000002.html",
+            "https://cq.ke.com/ershoufang/106118000003.html",
+            "https://cq.ke.com/ershoufang/106118000004.html",
+            "https://cq.ke.com/ershoufang/106118000005.html",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn server_errors_are_retried() {
+    let server = MockServer::start().await.unwrap();
+    server.route_sequence(
+        ERSHOUFANG_PG1,
+        vec![
+            MockResponse::Status(503),
+            MockResponse::Status(500),
+            MockResponse::Html(fixture("ershoufang_pg1.html")),
+        ],
+    );
+    server.route(ERSHOUFANG_PG2, MockResponse::Html(fixture("ershoufang_pg2.html")));
+
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(4));
+    let mut checkpoint = Checkpoint::open(&temp_dir("retry"), "ershoufang", false).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    assert_eq!(report.items.len(), 3);
+    assert!(report.failed_pages.is_empty());
+    assert_eq!(server.request_count(ERSHOUFANG_PG1), 3);
+}
+
+#[tokio::test]
+async fn captcha_page_is_recorded_as_failed_page() {
+    let server = MockServer::start().await.unwrap();
+    server.route(ERSHOUFANG_PG1, MockResponse::Html(fixture("ershoufang_pg1.html")));
+    server.route(ERSHOUFANG_PG2, MockResponse::CaptchaPage);
+
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(2));
+    let mut checkpoint = Checkpoint::open(&temp_dir("captcha"), "ershoufang", false).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    assert_eq!(report.items.len(), 2);
+    assert_eq!(report.failed_pages.len(), 1);
+    let failed = &report.failed_pages[0];
+    assert_eq!(failed.page, 2);
+    assert_eq!(failed.attempts, 2);
+    assert!(failed.url.ends_with(ERSHOUFANG_PG2));
+    assert_eq!(failed.error, ScrapeError::Captcha.to_string());
+}
+
+#[tokio::test]
+async fn captcha_redirect_is_detected() {
+    let server = MockServer::start().await.unwrap();
+    server.route(ERSHOUFANG_PG1, MockResponse::CaptchaRedirect);
+
+    let scraper = ErshoufangScraper::new(fetcher());
+    let url = ershoufang_search(&server).page_url(1);
+    let error = scraper.scrape_ershoufang_page(&url).await.unwrap_err();
+
+    assert!(matches!(error, ScrapeError::Captcha), "{:?}", error);
+    // 验证页本身不应该被请求
+    assert_eq!(server.requests(), [ERSHOUFANG_PG1]);
+}
+
+#[tokio::test]
+async fn plain_redirect_is_followed() {
+    let server = MockServer::start().await.unwrap();
+    // 缺少结尾 / 时 301 到规范地址，跳转地址的查询参数中带 login 也不算登录跳转
+    server.route("/ershoufang/dazhulin/pg1", MockResponse::Redirect {
+        status: 301,
+        location: format!("{}?from=login", ERSHOUFANG_PG1),
+    });
+    server.route(ERSHOUFANG_PG1, MockResponse::Html(fixture("ershoufang_pg1.html")));
+    server.route("/ershoufang/dazhulin/pg2/", MockResponse::Status(301));
+
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(4));
+    let page = scraper
+        .scrape_ershoufang_page(&format!("{}/ershoufang/dazhulin/pg1", server.base_url()))
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 2);
+    assert_eq!(server.requests(), ["/ershoufang/dazhulin/pg1".to_string(), format!("{}?from=login", ERSHOUFANG_PG1)]);
+
+    // 没有 Location 的 3xx 按普通 HTTP 错误处理，不会中止任务
+    let error = scraper
+        .scrape_ershoufang_page(&format!("{}{}", server.base_url(), ERSHOUFANG_PG2))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ScrapeError::Http(status) if status == StatusCode::MOVED_PERMANENTLY), "{:?}", error);
+    assert_ne!(error.class(), ErrorClass::Fatal);
+}
+
+#[tokio::test]
+async fn redirect_with_captcha_in_query_is_followed() {
+    let server = MockServer::start().await.unwrap();
+    // 跳转地址的查询参数中带 captcha 不算人机验证跳转
+    server.route("/ershoufang/dazhulin/pg1", MockResponse::Redirect {
+        status: 301,
+        location: format!("{}?from=captcha", ERSHOUFANG_PG1),
+    });
+    server.route(ERSHOUFANG_PG1, MockResponse::Html(fixture("ershoufang_pg1.html")));
+
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(1));
+    let page = scraper
+        .scrape_ershoufang_page(&format!("{}/ershoufang/dazhulin/pg1", server.base_url()))
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 2);
+    assert_eq!(server.requests(), ["/ershoufang/dazhulin/pg1".to_string(), format!("{}?from=captcha", ERSHOUFANG_PG1)]);
+}
+
+#[tokio::test]
+async fn login_redirect_aborts_crawl() {
+    let server = MockServer::start().await.unwrap();
+    server.route(ERSHOUFANG_PG1, MockResponse::LoginRedirect);
+
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(4));
+    let url = ershoufang_search(&server).page_url(1);
+    let error = scraper.scrape_ershoufang_page(&url).await.unwrap_err();
+    assert!(matches!(error, ScrapeError::LoginRequired { .. }), "{:?}", error);
+
+    let mut checkpoint = Checkpoint::open(&temp_dir("login"), "ershoufang", false).unwrap();
+    let result = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await;
+    assert!(result.is_err());
+    // 需要登录时不重试
+    assert_eq!(server.request_count(ERSHOUFANG_PG1), 2);
+}
+
+#[tokio::test]
+async fn invalid_url_is_not_retried() {
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(4));
+    let error = scraper.scrape_ershoufang_page("cq.ke.com/ershoufang/dazhulin/pg1/").await.unwrap_err();
+
+    assert!(matches!(error, ScrapeError::InvalidUrl { .. }), "{:?}", error);
+    assert_eq!(error.class(), ErrorClass::Permanent);
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let server = MockServer::start().await.unwrap();
+    server.route(ERSHOUFANG_PG1, MockResponse::Html(fixture("ershoufang_pg1.html")));
+    server.route(ERSHOUFANG_PG2, MockResponse::Status(404));
+
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(4));
+    let mut checkpoint = Checkpoint::open(&temp_dir("not_found"), "ershoufang", false).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    assert_eq!(report.failed_pages.len(), 1);
+    assert_eq!(report.failed_pages[0].attempts, 1);
+    assert_eq!(server.request_count(ERSHOUFANG_PG2), 1);
+}
+
+#[tokio::test]
+async fn failed_first_page_marks_search_incomplete() {
+    let server = MockServer::start().await.unwrap();
+    server.route(ERSHOUFANG_PG1, MockResponse::Status(500));
+    server.route(ERSHOUFANG_PG2, MockResponse::Html(fixture("ershoufang_pg2.html")));
+
+    let dir = temp_dir("first_page_failed");
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(2));
+    let mut checkpoint = Checkpoint::open(&dir, "ershoufang", false).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    // 页数未知，不能当作只有一页而悄悄结束
+    assert!(report.items.is_empty());
+    assert_eq!(report.failed_pages.len(), 1);
+    assert!(report.failed_pages[0].search_incomplete);
+    assert_eq!(server.request_count(ERSHOUFANG_PG2), 0);
+    checkpoint.finish(true).unwrap();
+    let state = fs::read_to_string(dir.join("ershoufang.state.json")).unwrap();
+    assert!(state.contains("\"search_incomplete\": true"), "{}", state);
+
+    // 恢复时重新爬取第一页并继续翻页
+    server.route(ERSHOUFANG_PG1, MockResponse::Html(fixture("ershoufang_pg1.html")));
+    let mut checkpoint = Checkpoint::open(&dir, "ershoufang", true).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+    assert_eq!(report.items.len(), 3);
+    assert!(report.failed_pages.is_empty());
+}
+
+#[tokio::test]
+async fn resume_drops_torn_last_line_of_partial_results() {
+    let server = MockServer::start().await.unwrap();
+    server.route(ERSHOUFANG_PG1, MockResponse::Html(fixture("ershoufang_pg1.html")));
+    server.route(ERSHOUFANG_PG2, MockResponse::Status(500));
+
+    let dir = temp_dir("torn_partial");
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(1));
+    let mut checkpoint = Checkpoint::open(&dir, "ershoufang", false).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+    assert_eq!(report.failed_pages.len(), 1);
+    checkpoint.finish(true).unwrap();
+
+    // 模拟进程在写入第二页的记录时退出，留下写了一半的一行
+    let partial_path = dir.join("ershoufang.partial.jsonl");
+    let mut partial = fs::read_to_string(&partial_path).unwrap();
+    partial.push_str(r#"{"id":"106118000003","title":"#);
+    fs::write(&partial_path, partial).unwrap();
+
+    server.route(ERSHOUFANG_PG2, MockResponse::Html(fixture("ershoufang_pg2.html")));
+    let mut checkpoint = Checkpoint::open(&dir, "ershoufang", true).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+    let ids: Vec<&str> = report.items.iter().filter_map(|item| item.id.as_ref()).map(|id| id.as_str()).collect();
+    assert_eq!(ids, ["106118000001", "106118000002", "106118000003"]);
+    assert_eq!(server.request_count(ERSHOUFANG_PG1), 1);
+
+    // 截断后文件中的每一行都是完整的记录，再次恢复时第二页的记录不会丢失
+    let partial = fs::read_to_string(&partial_path).unwrap();
+    assert!(partial.lines().all(|line| serde_json::from_str::<serde_json::Value>(line).is_ok()));
+    drop(checkpoint);
+    let checkpoint = Checkpoint::open(&dir, "ershoufang", true).unwrap();
+    assert_eq!(checkpoint.load_items::<ErshoufangData>().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn chengjiao_crawl_single_page() {
+    let server = MockServer::start().await.unwrap();
+    server.route("/chengjiao/dazhulin/pg1/", MockResponse::Html(fixture("chengjiao_pg1.html")));
+
+    let search = SearchUrl::new("cq", ListingKind::Chengjiao)
+        .area("dazhulin")
+        .base_url(&server.base_url());
+    let scraper = ChengjiaoScraper::new(fetcher()).with_retry_policy(retry(1));
+    let mut checkpoint = Checkpoint::open(&temp_dir("chengjiao"), "chengjiao", false).unwrap();
+    let report = scraper.scrape_multiple_pages(&search, 1, None, &mut checkpoint).await.unwrap();
+
+    assert_eq!(report.items.len(), 2);
+    assert_eq!(report.items[0].title, "金科天籁城 3室2厅 89.5平米");
+    assert_eq!(report.items[0].deal_date, NaiveDate::from_ymd_opt(2024, 5, 12));
+    assert_eq!(report.items[0].deal_price, Some(118.0));
+    assert_eq!(report.items[0].list_price, Some(125.0));
+    assert_eq!(report.items[0].unit_price, Some(13185.0));
+    assert_eq!(report.items[0].deal_cycle_days, Some(46));
+    assert_eq!(report.items[0].discount, Some(7.0));
+    assert_eq!(report.items[0].floor.position, Some(FloorPosition::Middle));
+    assert_eq!(report.items[0].build_year, Some(2015));
+    assert_eq!(report.items[0].is_renovated, "精装");
+    assert_eq!(report.items[1].is_renovated, "简装");
+    assert_eq!(server.requests(), ["/chengjiao/dazhulin/pg1/"]);
+}
+
+#[tokio::test]
+async fn chengjiao_details_are_joined_to_list_rows() {
+    let server = MockServer::start().await.unwrap();
+    server.route("/chengjiao/dazhulin/pg1/", MockResponse::Html(fixture("chengjiao_pg1.html")));
+    server.route(
+        "/chengjiao/106117000001.html",
+        MockResponse::Html(detail_fixture("chengjiao_106117000001.html")),
+    );
+    // 第二条记录的详情页不存在
+
+    let search = SearchUrl::new("cq", ListingKind::Chengjiao)
+        .area("dazhulin")
+        .base_url(&server.base_url());
+    let scraper = ChengjiaoScraper::new(fetcher()).with_retry_policy(retry(1));
+    let mut checkpoint = Checkpoint::open(&temp_dir("chengjiao_detail"), "chengjiao", false).unwrap();
+    let report = scraper.scrape_multiple_pages(&search, 1, None, &mut checkpoint).await.unwrap();
+    assert_eq!(report.items[0].url, "https://cq.ke.com/chengjiao/106117000001.html");
+    assert_eq!(report.items[0].community_id.as_deref(), Some("3611056497876"));
+    // 列表项中没有小区链接时，由详情页补全小区编号
+    let mut items = report.items;
+    items[0].community_id = None;
+
+    let detail_scraper = ChengjiaoDetailScraper::new(fetcher())
+        .with_retry_policy(retry(3))
+        .with_base_url(Some(server.base_url()));
+    let details = detail_scraper.scrape_details(items).await.unwrap();
+
+    assert_eq!(details.records.len(), 2);
+    let first = &details.records[0];
+    assert_eq!(first.summary.title, "金科天籁城 3室2厅 89.5平米");
+    assert_eq!(first.summary.community_id.as_deref(), Some("3611056497876"));
+    let detail = first.detail.as_ref().unwrap();
+    assert_eq!(detail.listed_on, NaiveDate::from_ymd_opt(2024, 3, 27));
+    assert_eq!(detail.price_adjustments, Some(2));
+    assert_eq!(detail.showings, Some(15));
+    assert_eq!(detail.has_elevator, Some(true));
+    assert_eq!(detail.history.len(), 2);
+
+    assert!(details.records[1].detail.is_none());
+    assert_eq!(details.failed.len(), 1);
+    assert!(details.failed[0].url.ends_with("/chengjiao/106117000002.html"));
+    // 404 不重试
+    assert_eq!(server.request_count("/chengjiao/106117000002.html"), 1);
+}
+
+#[tokio::test]
+async fn chengjiao_detail_of_another_deal_is_rejected() {
+    let server = MockServer::start().await.unwrap();
+    server.route("/chengjiao/dazhulin/pg1/", MockResponse::Html(fixture("chengjiao_pg1.html")));
+    // 两条记录都返回第一条成交记录的详情页
+    for id in ["106117000001", "106117000002"] {
+        server.route(
+            &format!("/chengjiao/{}.html", id),
+            MockResponse::Html(detail_fixture("chengjiao_106117000001.html")),
+        );
+    }
+
+    let search = SearchUrl::new("cq", ListingKind::Chengjiao)
+        .area("dazhulin")
+        .base_url(&server.base_url());
+    let mut checkpoint = Checkpoint::open(&temp_dir("chengjiao_detail_mismatch"), "chengjiao", false).unwrap();
+    let mut items = ChengjiaoScraper::new(fetcher())
+        .with_retry_policy(retry(1))
+        .scrape_multiple_pages(&search, 1, None, &mut checkpoint)
+        .await
+        .unwrap()
+        .items;
+    for item in &mut items {
+        item.community_id = None;
+    }
+
+    let details = ChengjiaoDetailScraper::new(fetcher())
+        .with_retry_policy(retry(3))
+        .with_base_url(Some(server.base_url()))
+        .scrape_details(items)
+        .await
+        .unwrap();
+
+    let first = &details.records[0];
+    assert_eq!(first.detail.as_ref().and_then(|d| d.deal_id.as_ref()).map(|id| id.as_str()), Some("106117000001"));
+    assert_eq!(first.summary.community_id.as_deref(), Some("3611056497876"));
+    // 编号不一致的详情页不合并、不重试，也不用来补全小区编号
+    let second = &details.records[1];
+    assert!(second.detail.is_none());
+    assert_eq!(second.summary.community_id, None);
+    assert_eq!(server.request_count("/chengjiao/106117000002.html"), 1);
+    let mismatch = ScrapeError::Parse {
+        field: "deal_id",
+        reason: "详情页的成交编号 106117000001 与列表记录 106117000002 不一致".to_string(),
+    };
+    assert_eq!(details.failed.len(), 1);
+    assert_eq!(details.failed[0].error, mismatch.to_string());
+}
+
+#[tokio::test]
+async fn ershoufang_details_are_retried_and_joined() {
+    let server = MockServer::start().await.unwrap();
+    server.route(ERSHOUFANG_PG1, MockResponse::Html(fixture("ershoufang_pg1.html")));
+    server.route(ERSHOUFANG_PG2, MockResponse::Html(fixture("ershoufang_pg2.html")));
+    // 第一条先遇到一次 503，第二条需要人机验证，第三条返回的是第一条房源的详情页
+    server.route_sequence(
+        "/ershoufang/106118000001.html",
+        vec![MockResponse::Status(503), MockResponse::Html(detail_fixture("ershoufang_106118000001.html"))],
+    );
+    server.route("/ershoufang/106118000002.html", MockResponse::CaptchaPage);
+    server.route(
+        "/ershoufang/106118000003.html",
+        MockResponse::Html(detail_fixture("ershoufang_106118000001.html")),
+    );
+
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(1));
+    let mut checkpoint = Checkpoint::open(&temp_dir("ershoufang_detail"), "ershoufang", false).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    let detail_scraper = ErshoufangDetailScraper::new(fetcher())
+        .with_retry_policy(retry(2))
+        .with_concurrency(2)
+        .with_base_url(Some(server.base_url()));
+    let details = detail_scraper.scrape_details(report.items).await.unwrap();
+
+    assert_eq!(details.records.len(), 3);
+    let detail = details.records[0].detail.as_ref().unwrap();
+    assert_eq!(detail.listing_id.as_ref().map(|id| id.as_str()), Some("106118000001"));
+    assert!(details.records[0].summary.url.ends_with("106118000001.html"));
+    assert_eq!(detail.community_id.as_deref(), Some("3611056497876"));
+    assert_eq!(detail.agent.as_ref().map(|a| a.name.as_str()), Some("王小明"));
+    assert_eq!(detail.mortgaged, Some(true));
+    assert_eq!(server.request_count("/ershoufang/106118000001.html"), 2);
+
+    assert!(details.records[1].detail.is_none());
+    // 编号不一致的详情页不合并，也不重试
+    assert!(details.records[2].detail.is_none());
+    assert_eq!(server.request_count("/ershoufang/106118000003.html"), 1);
+    let failed: Vec<&str> = details.failed.iter().map(|f| f.error.as_str()).collect();
+    let mismatch = ScrapeError::Parse {
+        field: "listing_id",
+        reason: "详情页的房源编号 106118000001 与列表记录 106118000003 不一致".to_string(),
+    };
+    assert_eq!(failed, [ScrapeError::Captcha.to_string(), mismatch.to_string()]);
+}
+
+fn zufang_search(server: &MockServer) -> SearchUrl {
+    SearchUrl::new("cq", ListingKind::Zufang)
+        .area("dazhulin")
+        .base_url(&server.base_url())
+}
+
+#[tokio::test]
+async fn zufang_crawl_resolves_relative_links() {
+    let server = MockServer::start().await.unwrap();
+    server.route("/zufang/dazhulin/pg1/", MockResponse::Html(fixture("zufang_pg1.html")));
+
+    let scraper = ZufangScraper::new(fetcher()).with_retry_policy(retry(1));
+    let mut checkpoint = Checkpoint::open(&temp_dir("zufang"), "zufang", false).unwrap();
+    let report = scraper.scrape_multiple_pages(&zufang_search(&server), 1, None, &mut checkpoint).await.unwrap();
+
+    // 广告位被跳过
+    assert_eq!(report.items.len(), 3);
+    let first = &report.items[0];
+    assert_eq!(first.id.as_ref().map(|id| id.as_str()), Some("CQ1234567890"));
+    assert_eq!(first.url, format!("{}/zufang/CQ1234567890.html", server.base_url()));
+    assert_eq!(first.rental_type, Some(RentalType::Whole));
+    assert_eq!(first.rent, Some(2800.0));
+    assert_eq!(first.area, Some(89.5));
+    assert_eq!(first.layout, "3室2厅2卫");
+    assert_eq!(first.orientation, "南 北");
+    assert_eq!(first.floor.position, Some(FloorPosition::Middle));
+    assert_eq!(first.floor.total_floors, Some(18));
+    assert_eq!(first.community, "金科天籁城");
+    assert_eq!(first.community_id.as_deref(), Some("3611056497876"));
+    assert_eq!(first.region.describe(), "渝北/大竹林");
+    assert_eq!(first.listed_on, Some(Local::now().date_naive() - Duration::days(3)));
+    assert_eq!(report.items[1].rental_type, Some(RentalType::Shared));
+    assert_eq!(server.requests(), ["/zufang/dazhulin/pg1/"]);
+}
+
+#[tokio::test]
+async fn loupan_crawl_joins_project_details() {
+    let server = MockServer::start().await.unwrap();
+    server.route("/loupan/dazhulin/pg1/", MockResponse::Html(fixture("loupan_pg1.html")));
+    server.route("/loupan/p_jktlcbkcvf/xiangqing/", MockResponse::Html(detail_fixture("loupan_p_jktlcbkcvf_xiangqing.html")));
+
+    let search = SearchUrl::new("cq", ListingKind::Loupan)
+        .area("dazhulin")
+        .base_url(&server.base_url());
+    let mut checkpoint = Checkpoint::open(&temp_dir("loupan"), "loupan", false).unwrap();
+    let report = LoupanScraper::new(fetcher())
+        .with_retry_policy(retry(1))
+        .scrape_multiple_pages(&search, 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    // 分页信息中共 3 个楼盘、每页 10 个，只有一页
+    assert_eq!(report.items.len(), 3);
+    let first = &report.items[0];
+    assert_eq!(first.id.as_deref(), Some("p_jktlcbkcvf"));
+    assert_eq!(first.url, format!("{}/loupan/p_jktlcbkcvf/", server.base_url()));
+    assert_eq!(first.sale_status, Some(SaleStatus::OnSale));
+    assert_eq!(first.average_price, Some(15000.0));
+    assert_eq!((first.total_price_min, first.total_price_max), (Some(135.0), Some(210.0)));
+    assert_eq!((first.area_min, first.area_max), (Some(89.0), Some(143.0)));
+    assert_eq!(first.region.describe(), "渝北/大竹林");
+    assert_eq!(report.items[1].average_price, None);
+    assert_eq!(report.items[2].sale_status, Some(SaleStatus::SoldOut));
+
+    // 第二个楼盘返回的是第一个楼盘的详情页，第三个楼盘的详情页不存在
+    server.route("/loupan/p_lhysjbmzxq/xiangqing/", MockResponse::Html(detail_fixture("loupan_p_jktlcbkcvf_xiangqing.html")));
+    let details = LoupanDetailScraper::new(fetcher())
+        .with_retry_policy(retry(1))
+        .with_base_url(Some(server.base_url()))
+        .scrape_details(report.items)
+        .await
+        .unwrap();
+    assert_eq!(details.failed.len(), 2);
+    let detail = details.records[0].detail.as_ref().unwrap();
+    assert_eq!(detail.developer.as_deref(), Some("重庆金科房地产开发有限公司"));
+    assert_eq!(detail.opening_date, NaiveDate::from_ymd_opt(2024, 5, 18));
+    assert_eq!(detail.planned_households, Some(1260));
+    assert_eq!(detail.property_fee, Some(2.8));
+    assert_eq!(detail.project_id.as_deref(), Some("p_jktlcbkcvf"));
+    assert!(details.records[1].detail.is_none());
+    let mismatch = ScrapeError::Parse {
+        field: "project_id",
+        reason: "详情页的楼盘编号 p_jktlcbkcvf 与列表记录 p_lhysjbmzxq 不一致".to_string(),
+    };
+    assert_eq!(details.failed[0].error, mismatch.to_string());
+}
+
+#[tokio::test]
+async fn community_table_links_listings_and_deals() {
+    let server = MockServer::start().await.unwrap();
+    server.route("/xiaoqu/dazhulin/pg1/", MockResponse::Html(fixture("xiaoqu_pg1.html")));
+    server.route("/xiaoqu/3611056497876/", MockResponse::Html(detail_fixture("xiaoqu_3611056497876.html")));
+    server.route(ERSHOUFANG_PG1, MockResponse::Html(fixture("ershoufang_pg1.html")));
+    server.route(ERSHOUFANG_PG2, MockResponse::Html(fixture("ershoufang_pg2.html")));
+    server.route("/chengjiao/dazhulin/pg1/", MockResponse::Html(fixture("chengjiao_pg1.html")));
+    server.route("/zufang/dazhulin/pg1/", MockResponse::Html(fixture("zufang_pg1.html")));
+    let dir = temp_dir("community");
+
+    let search = SearchUrl::new("cq", ListingKind::Xiaoqu)
+        .area("dazhulin")
+        .base_url(&server.base_url());
+    let mut checkpoint = Checkpoint::open(&dir, "xiaoqu", false).unwrap();
+    let xiaoqu = XiaoquScraper::new(fetcher())
+        .with_retry_policy(retry(1))
+        .scrape_multiple_pages(&search, 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+    assert_eq!(xiaoqu.items.len(), 2);
+    assert_eq!(xiaoqu.items[0].sold_count, Some(12));
+    // 第二个小区返回的是第一个小区的详情页，不合并，只保留列表上的信息
+    server.route("/xiaoqu/3611056497877/", MockResponse::Html(detail_fixture("xiaoqu_3611056497876.html")));
+    let details = XiaoquDetailScraper::new(fetcher())
+        .with_retry_policy(retry(1))
+        .with_base_url(Some(server.base_url()))
+        .scrape_details(xiaoqu.items)
+        .await
+        .unwrap();
+    assert_eq!(details.failed.len(), 1);
+    assert!(details.failed[0].error.contains("详情页的小区编号 3611056497876 与列表记录 3611056497877 不一致"));
+    assert!(details.records[1].detail.is_none());
+
+    let mut checkpoint = Checkpoint::open(&dir, "ershoufang", false).unwrap();
+    let listings = ErshoufangScraper::new(fetcher())
+        .with_retry_policy(retry(1))
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+    let search = SearchUrl::new("cq", ListingKind::Chengjiao)
+        .area("dazhulin")
+        .base_url(&server.base_url());
+    let mut checkpoint = Checkpoint::open(&dir, "chengjiao", false).unwrap();
+    let deals = ChengjiaoScraper::new(fetcher())
+        .with_retry_policy(retry(1))
+        .scrape_multiple_pages(&search, 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    let mut checkpoint = Checkpoint::open(&dir, "zufang", false).unwrap();
+    let rentals = ZufangScraper::new(fetcher())
+        .with_retry_policy(retry(1))
+        .scrape_multiple_pages(&zufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    // 第一条成交有小区编号，标题中的小区名与小区表不一致时仍按编号关联；第二条没有编号，按名称关联
+    let mut deals = deals.items;
+    deals[0].community = "金科天籁城一期".to_string();
+
+    let mut table = CommunityTable::new(details.records);
+    table.link_rentals(&rentals.items);
+    table.link_listings(&listings.items);
+    table.link_deals(&deals);
+    // 多个输入文件中重复出现的记录按编号只关联一次，没有编号的记录每次都关联
+    table.link_rentals(&rentals.items);
+    table.link_listings(&listings.items);
+    let mut without_id = deals[1].clone();
+    without_id.id = None;
+    table.link_deals(&[deals[0].clone(), without_id.clone(), without_id]);
+
+    let community = table.get("3611056497876").unwrap();
+    assert_eq!(community.name, "金科天籁城");
+    assert_eq!(community.building_count, Some(28));
+    assert_eq!(community.household_count, Some(3245));
+    assert_eq!(community.developer.as_deref(), Some("重庆金科房地产开发有限公司"));
+    assert_eq!(community.property_fee_max, Some(2.5));
+    assert_eq!(community.listings.iter().map(|id| id.as_str()).collect::<Vec<_>>(), ["106118000001"]);
+    assert_eq!(community.deals.iter().map(|id| id.as_str()).collect::<Vec<_>>(), ["106117000001"]);
+    assert_eq!(community.listing_unit_price, Some(13408.0));
+    assert_eq!(community.deal_unit_price, Some(13185.0));
+    // 合租房源关联到小区，但只有整租参与租金计算：2800 / 89.5 ≈ 31.28 元/平米/月
+    assert_eq!(community.rentals.len(), 2);
+    assert_eq!(community.rent_per_square_meter, Some(31.28));
+    // 租金先于在售关联，回报率在关联在售后按在售均价重新计算：31.28 × 12 ÷ 13408
+    assert_eq!(community.rental_yield, Some(2.8));
+
+    let without_detail = table.get("3611056497877").unwrap();
+    assert_eq!(without_detail.average_price, Some(15210.0));
+    assert_eq!(without_detail.developer, None);
+    assert_eq!(without_detail.deals.len(), 1);
+    // 2100 / 70 = 30 元/平米/月，按关联在售的均价计算：30 × 12 ÷ 12537
+    assert_eq!(without_detail.rent_per_square_meter, Some(30.0));
+    assert_eq!(without_detail.rental_yield, Some(2.87));
+    // 中铁任之健康城不在小区表中
+    assert_eq!(table.unmatched_listings, 1);
+    assert_eq!(table.unmatched_deals, 0);
+    assert_eq!(table.name_matched_deals, 3);
+    assert_eq!(table.unmatched_rentals, 0);
+}
+
+#[tokio::test]
+async fn price_history_tracks_cuts_across_runs() {
+    // 三次爬取之间第一套房源从 120 万降到 115 万，再降到 112 万
+    let pg1 = fixture("ershoufang_pg1.html");
+    let reduced = |price: &str, followers: &str| {
+        pg1.replace(r#"<span class="">120</span>"#, &format!(r#"<span class="">{}</span>"#, price))
+            .replace("12人关注", followers)
+    };
+    let server = MockServer::start().await.unwrap();
+    server.route_sequence(ERSHOUFANG_PG1, vec![
+        MockResponse::Html(pg1.clone()),
+        MockResponse::Html(reduced("115", "20人关注")),
+        MockResponse::Html(reduced("112", "26人关注")),
+    ]);
+    server.route(ERSHOUFANG_PG2, MockResponse::Html(fixture("ershoufang_pg2.html")));
+
+    let dir = temp_dir("price_history");
+    let path = dir.join("ershoufang_history.json");
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(1));
+    for day in 1..=3 {
+        let mut checkpoint = Checkpoint::open(&dir, "ershoufang", false).unwrap();
+        let report = scraper
+            .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+            .await
+            .unwrap();
+        // 每次运行都重新打开历史文件，模拟分开的多次运行
+        let mut history = PriceHistory::open(&path).unwrap();
+        let observed_at = Local.with_ymd_and_hms(2024, 6, day, 9, 0, 0).unwrap();
+        assert_eq!(history.record(&report.items, observed_at), 3);
+        history.save().unwrap();
+    }
+
+    let history = PriceHistory::open(&path).unwrap();
+    assert_eq!(history.listings.len(), 3);
+    let listing = history.get("106118000001").unwrap();
+    assert_eq!(listing.observations.len(), 3);
+    assert_eq!(listing.latest().unwrap().follower_count, Some(26));
+    assert_eq!(listing.cut_count(), 2);
+    assert_eq!(listing.cumulative_discount(), Some(8.0));
+    assert_eq!(listing.cumulative_discount_percent(), Some(6.67));
+
+    let cuts = history.price_cuts();
+    assert_eq!(cuts.len(), 2);
+    assert_eq!((cuts[0].previous_price, cuts[0].price), (120.0, 115.0));
+    assert_eq!(cuts[0].observed_at, Local.with_ymd_and_hms(2024, 6, 2, 9, 0, 0).unwrap());
+    assert_eq!(cuts[1].amount(), 3.0);
+
+    // 价格没有变化的房源没有降价
+    let unchanged = history.get("106118000002").unwrap();
+    assert_eq!(unchanged.cut_count(), 0);
+    assert_eq!(unchanged.cumulative_discount(), Some(0.0));
+}
+
+#[tokio::test]
+async fn archived_pages_can_be_replayed() {
+    let archive = Archive::open(&temp_dir("replay")).unwrap();
+    for (page, name) in [(1, "ershoufang_pg1.html"), (2, "ershoufang_pg2.html")] {
+        let url = format!("https://cq.ke.com/ershoufang/dazhulin/pg{}/", page);
+        archive.store(&ArchivedPage::new(&url, 200, BTreeMap::new(), &fixture(name))).unwrap();
+    }
+
+    let server = MockServer::start().await.unwrap();
+    assert_eq!(server.serve_archive(&archive).unwrap(), 2);
+
+    let scraper = ErshoufangScraper::new(fetcher()).with_retry_policy(retry(1));
+    let mut checkpoint = Checkpoint::open(&temp_dir("replay_checkpoint"), "ershoufang", false).unwrap();
+    let report = scraper
+        .scrape_multiple_pages(&ershoufang_search(&server), 1, None, &mut checkpoint)
+        .await
+        .unwrap();
+
+    assert_eq!(report.items.len(), 3);
+}
+
+#[test]
+fn reparse_keeps_latest_snapshot_and_skips_detail_pages() {
+    let archive = Archive::open(&temp_dir("reparse")).unwrap();
+    let store = |url: &str, fetched_at: u64, body: &str| {
+        let mut page = ArchivedPage::new(url, 200, BTreeMap::new(), body);
+        page.fetched_at = fetched_at;
+        archive.store(&page).unwrap();
+    };
+    // 同一列表页的两次抓取，第二次第一套房源降到 115 万
+    let pg1 = fixture("ershoufang_pg1.html");
+    store("https://cq.ke.com/ershoufang/dazhulin/pg1/", 1_717_200_000_000, &pg1);
+    store(
+        "https://cq.ke.com/ershoufang/dazhulin/pg1/",
+        1_717_286_400_000,
+        &pg1.replace(r#"<span class="">120</span>"#, r#"<span class="">115</span>"#),
+    );
+    store("https://cq.ke.com/chengjiao/dazhulin/pg1/", 1_717_200_000_000, &fixture("chengjiao_pg1.html"));
+    // 详情页不是列表页
+    store("https://cq.ke.com/ershoufang/106118000001.html", 1_717_200_000_000, &detail_fixture("ershoufang_106118000001.html"));
+    store("https://cq.ke.com/chengjiao/106117000001.html", 1_717_200_000_000, &detail_fixture("chengjiao_106117000001.html"));
+    store("https://cq.ke.com/xiaoqu/3611056497876/", 1_717_200_000_000, &detail_fixture("xiaoqu_3611056497876.html"));
+    store(
+        "https://cq.fang.ke.com/loupan/p_jktlcbkcvf/xiangqing/",
+        1_717_200_000_000,
+        &detail_fixture("loupan_p_jktlcbkcvf_xiangqing.html"),
+    );
+
+    let output_dir = temp_dir("reparse_output");
+    let summary = reparse_archive(&archive, &output_dir, &[OutputFormat::Json]).unwrap();
+    assert_eq!(summary.parsed_pages, 3);
+    assert_eq!(summary.skipped_pages, 4);
+
+    let load = |name: &str| -> Vec<serde_json::Value> {
+        serde_json::from_str(&fs::read_to_string(output_dir.join(name)).unwrap()).unwrap()
+    };
+    let ershoufang = load("reparse_ershoufang_data.json");
+    assert_eq!(ershoufang.len(), 2);
+    assert_eq!(ershoufang[0]["id"], "106118000001");
+    assert_eq!(ershoufang[0]["total_price"], 115.0);
+    assert!(!load("reparse_chengjiao_data.json").is_empty());
+    assert!(!output_dir.join("reparse_xiaoqu_data.json").exists());
+    assert!(!output_dir.join("reparse_loupan_data.json").exists());
+}
+
+#[test]
+fn reparse_reads_archives_from_mirror_hosts() {
+    let archive = Archive::open(&temp_dir("reparse_mirror")).unwrap();
+    // 通过 --base-url 爬取本地模拟服务器时归档的地址
+    for (url, body) in [
+        ("http://127.0.0.1:8080/ershoufang/dazhulin/pg1/", fixture("ershoufang_pg1.html")),
+        ("http://127.0.0.1:8080/zufang/dazhulin/pg1/", fixture("zufang_pg1.html")),
+        ("http://127.0.0.1:8080/ershoufang/106118000001.html", detail_fixture("ershoufang_106118000001.html")),
+    ] {
+        archive.store(&ArchivedPage::new(url, 200, BTreeMap::new(), &body)).unwrap();
+    }
+
+    let output_dir = temp_dir("reparse_mirror_output");
+    let summary = reparse_archive(&archive, &output_dir, &[OutputFormat::Json]).unwrap();
+    assert_eq!(summary.parsed_pages, 2);
+    assert_eq!(summary.skipped_pages, 1);
+
+    let load = |name: &str| -> Vec<serde_json::Value> {
+        serde_json::from_str(&fs::read_to_string(output_dir.join(name)).unwrap()).unwrap()
+    };
+    assert_eq!(load("reparse_ershoufang_data.json").len(), 2);
+    let zufang = load("reparse_zufang_data.json");
+    assert_eq!(zufang.len(), 3);
+    assert_eq!(zufang[0]["url"], "http://127.0.0.1:8080/zufang/CQ1234567890.html");
+}